@@ -1,25 +1,448 @@
 //! Order floating point numbers, into this ordering:
 //!
 //!    NaN | -Infinity | x < 0 | -0 | +0 | x > 0 | +Infinity | NaN
+//!
+//! The NaN on the left have their sign bit set, and the NaN on the right
+//! don't, so this is the IEEE 754 totalOrder predicate. Enabling the
+//! `std-total-cmp` feature makes `Ord` call the standard library's
+//! `total_cmp` instead of comparing the crate's own integer keys; the
+//! resulting order is the same either way.
+//!
+//! Enabling the `nan-at-top` feature instead groups every NaN together,
+//! regardless of sign or payload:
+//!
+//!    -Infinity | x < 0 | -0 | +0 | x > 0 | +Infinity | NaN
+//!
+//! All NaN then compare equal to each other and hash the same. This feature
+//! takes precedence over `std-total-cmp`.
+//!
+//! The crate is `no_std`. Helpers that allocate, such as `argsort`, `dedup`
+//! and `top_k`, only need the `alloc` feature, which is on by default and
+//! works with any global allocator. The `std` feature adds float math that
+//! has no `core` implementation; `libm` provides the same math without
+//! `std`. With neither `std` nor `alloc`, everything that does not allocate
+//! is still available.
+//!
+//! On a nightly compiler, the `step` feature implements `Step`, so a range
+//! of `FloatOrd`s iterates over every representable float in between.
+//! With `nan-at-top`, where every NaN is equal, NaN is the one step above
+//! Infinity.
 
 #![no_std]
+#![cfg_attr(feature="step", feature(step_trait))]
 
+#[cfg(feature="std")]
+extern crate std;
 #[cfg(feature="pdqsort")]
 extern crate pdqsort;
+#[cfg(feature="serde")]
+extern crate serde;
+#[cfg(feature="alloc")]
+extern crate alloc;
+#[cfg(feature="alloc")]
+use alloc::vec::Vec;
+#[cfg(feature="alloc")]
+use alloc::collections::BinaryHeap;
+#[cfg(feature="alloc")]
+use core::iter::FromIterator;
+#[cfg(feature="half")]
+extern crate half;
+#[cfg(feature="num-traits")]
+extern crate num_traits;
+#[cfg(feature="rayon")]
+extern crate rayon;
+#[cfg(feature="libm")]
+extern crate libm;
+#[cfg(feature="num-complex")]
+extern crate num_complex;
 
-use core::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+use core::cmp::{self, Eq, Ord, Ordering, PartialEq, PartialOrd};
+use core::convert::TryFrom;
+use core::fmt;
 use core::hash::{Hash, Hasher};
+use core::iter::{Product, Sum};
+use core::num::ParseFloatError;
+use core::marker::PhantomData;
+use core::ops::Bound;
+#[cfg(any(feature="pdqsort", feature="alloc"))]
+use core::slice;
+use core::str::FromStr;
 use core::mem::transmute;
 
 /// A wrapper for floats, that implements total equality and ordering
 /// and hashing.
-#[derive(Clone, Copy, Debug)]
+///
+/// A `FloatOrd` can also be compared directly against a bare float of the
+/// same type. The comparison still uses the total ordering, so it can
+/// disagree with comparing the two floats: `FloatOrd(-0.0) == 0.0` is
+/// `false`, and `FloatOrd(f64::NAN) == f64::NAN` is `true`.
+///
+/// # Layout
+///
+/// `FloatOrd<T>` is `#[repr(transparent)]`, so it has the same size,
+/// alignment and ABI as `T`. It is sound to reinterpret a `T` as a
+/// `FloatOrd<T>` and back, including behind references and in slices;
+/// `FloatOrd::from_ref` and `as_ord_slice` do this without `unsafe`.
+///
+/// # Debug
+///
+/// `Debug` is implemented for the float types and arrays of them, and
+/// shows the sign of zeros and the sign and bits of NaN. Since 0.2 it is no
+/// longer derived, so a `FloatOrd<T>` around any other `T` does not
+/// implement `Debug`; format the inner value instead.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
 pub struct FloatOrd<T>(pub T);
 
+/// The error returned by `FloatOrd::try_new` when given a NaN.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NanError;
+
+impl fmt::Display for NanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("value is NaN")
+    }
+}
+
+/// The error returned when narrowing a finite `FloatOrd<f64>` to
+/// `FloatOrd<f32>` overflows to infinity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverflowError;
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("value is out of range")
+    }
+}
+
+/// The error returned when decoding key bytes from a slice of the wrong
+/// length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyLengthError;
+
+impl fmt::Display for KeyLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("key has the wrong length")
+    }
+}
+
+/// The regions of the total ordering, as returned by
+/// `FloatOrd::classify_ord`.
+///
+/// The variants are declared, and so compare, in the same order as the
+/// values they describe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FloatClass {
+    /// NaN with the sign bit set.
+    NegativeNan,
+    /// -Infinity.
+    NegativeInfinity,
+    /// A finite value below zero, including subnormals.
+    Negative,
+    /// -0.
+    NegativeZero,
+    /// +0.
+    PositiveZero,
+    /// A finite value above zero, including subnormals.
+    Positive,
+    /// +Infinity.
+    PositiveInfinity,
+    /// NaN with the sign bit clear.
+    PositiveNan,
+}
+
+/// Comparison by the total ordering, for implementing `Ord` by hand.
+///
+/// `Ord` can't be derived for structs with float fields. This trait lets the
+/// fields be compared in place, without wrapping each one in `FloatOrd`:
+///
+/// ```
+/// use std::cmp::Ordering;
+/// use float_ord::TotalOrd;
+///
+/// #[derive(PartialEq)]
+/// struct Point {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// impl Eq for Point {}
+/// impl PartialOrd for Point {
+///     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+///         Some(self.cmp(other))
+///     }
+/// }
+/// impl Ord for Point {
+///     fn cmp(&self, other: &Self) -> Ordering {
+///         self.x.total_ordering(&other.x).then_with(|| self.y.total_ordering(&other.y))
+///     }
+/// }
+/// ```
+///
+/// Note that the derived `PartialEq` above still uses float equality. Define
+/// it with `total_ordering` too if NaN fields need to compare equal.
+pub trait TotalOrd {
+    /// Compare by the total ordering.
+    fn total_ordering(&self, other: &Self) -> Ordering;
+}
+
+/// Floats that `radix_sort` can sort.
+///
+/// This is implemented for every float type that `FloatOrd` supports.
+pub trait RadixKey: Copy {
+    /// The size of the key, in bytes.
+    const BYTES: usize;
+    /// Return the ordered bit representation, as returned by
+    /// `FloatOrd::to_ord_bits`, widened to 64 bits.
+    fn radix_key(self) -> u64;
+    /// Rebuild a float from a key returned by `radix_key`.
+    fn from_radix_key(key: u64) -> Self;
+}
+
+/// Floats that `canonicalize_slice` can rewrite.
+///
+/// This is implemented for every float type that `FloatOrd` supports.
+pub trait Canonicalize: Copy {
+    /// Return the value from `FloatOrd::canonicalize`.
+    fn canonicalize(self) -> Self;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+// Formats a float inside `FloatOrd`'s `Debug`.
+struct DebugFloat<T>(T);
+
+/// The float types that `FloatOrd` supports.
+///
+/// This lets generic helpers such as `sort_floats` tell a float apart from
+/// an array of floats. It is sealed, so it can't be implemented outside this
+/// crate.
+pub trait Float: Copy + sealed::Sealed {
+    /// Return `true` if the value is NaN.
+    fn is_nan(self) -> bool;
+    /// Return `true` if the sign bit is set, including for `-0` and NaN.
+    fn is_sign_negative(self) -> bool;
+    /// Return the value from `FloatOrd::within_ulps`.
+    fn within_ulps(self, other: Self, ulps: u64) -> bool;
+}
+
 macro_rules! float_ord_impl {
     ($f:ident, $i:ident, $n:expr) => {
         impl FloatOrd<$f> {
-            fn convert(self) -> $i {
+            /// Wrap `x`, returning `None` if it is NaN.
+            ///
+            /// Without NaN, the ordering of `FloatOrd` agrees with the
+            /// conventional IEEE one (apart from `-0 < +0`).
+            pub fn new_finite(x: $f) -> Option<FloatOrd<$f>> {
+                if x.is_nan() {
+                    None
+                } else {
+                    Some(FloatOrd(x))
+                }
+            }
+            /// Wrap `x`, returning `Err(NanError)` if it is NaN.
+            pub fn try_new(x: $f) -> Result<FloatOrd<$f>, NanError> {
+                Self::new_finite(x).ok_or(NanError)
+            }
+            /// Return `true` if the float is NaN.
+            pub fn is_nan(&self) -> bool {
+                self.0.is_nan()
+            }
+            /// Return `true` if the float is neither infinite nor NaN.
+            pub fn is_finite(&self) -> bool {
+                self.0.is_finite()
+            }
+            /// Return `true` if the float is positive or negative infinity.
+            pub fn is_infinite(&self) -> bool {
+                self.0.is_infinite()
+            }
+            /// Return `true` if the float has its sign bit set.
+            ///
+            /// This includes `-0` and negative NaN, which are exactly the
+            /// values that sort below `+0`.
+            pub fn is_sign_negative(&self) -> bool {
+                self.0.is_sign_negative()
+            }
+            /// Return the region of the total ordering this value falls in.
+            ///
+            /// Unlike `f64::classify`, this tells apart the signed zeros,
+            /// infinities and NaN.
+            pub fn classify_ord(&self) -> FloatClass {
+                let zero: $i = 1 << ($n - 1);
+                let key = self.total_order_key();
+                if self.0.is_nan() {
+                    if key < zero { FloatClass::NegativeNan } else { FloatClass::PositiveNan }
+                } else if self.0.is_infinite() {
+                    if key < zero {
+                        FloatClass::NegativeInfinity
+                    } else {
+                        FloatClass::PositiveInfinity
+                    }
+                } else if key == zero {
+                    FloatClass::PositiveZero
+                } else if key == zero - 1 {
+                    FloatClass::NegativeZero
+                } else if key < zero {
+                    FloatClass::Negative
+                } else {
+                    FloatClass::Positive
+                }
+            }
+            /// Compare using the IEEE 754 totalOrder predicate.
+            ///
+            /// Unless the `nan-at-top` feature is enabled, this gives the
+            /// same result as `Ord::cmp`. The NaN at each end of the crate's
+            /// ordering are not mirrored copies of one another: negative NaN
+            /// sorts lowest and positive NaN sorts highest, which is exactly
+            /// what totalOrder specifies.
+            pub fn total_cmp(&self, other: &Self) -> Ordering {
+                self.total_order_key().cmp(&other.total_order_key())
+            }
+            /// Compare in the reverse of the total ordering.
+            ///
+            /// This is `other.cmp(self)`, for sorting largest first.
+            pub fn reverse_cmp(&self, other: &Self) -> Ordering {
+                other.cmp(self)
+            }
+            /// Compare using the conventional IEEE 754 ordering, instead of
+            /// the total ordering.
+            ///
+            /// Returns `None` if either value is NaN, and treats `-0` and
+            /// `+0` as equal, just like comparing the bare floats.
+            pub fn ieee_partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                self.0.partial_cmp(&other.0)
+            }
+            /// Restrict a value to the interval `[min, max]`, according to
+            /// the total ordering.
+            ///
+            /// Unlike `f64::clamp`, a NaN is clamped like any other value,
+            /// and NaN bounds are allowed.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `min > max`.
+            pub fn clamp(self, min: Self, max: Self) -> Self {
+                Ord::clamp(self, min, max)
+            }
+            /// Replace NaN and the infinities with the given values, passing
+            /// finite values through unchanged.
+            pub fn clamp_to_finite(self, nan: $f, neg_inf: $f, pos_inf: $f) -> FloatOrd<$f> {
+                if self.0.is_nan() {
+                    FloatOrd(nan)
+                } else if self.0.is_infinite() {
+                    FloatOrd(if self.0.is_sign_negative() { neg_inf } else { pos_inf })
+                } else {
+                    self
+                }
+            }
+            /// Map `-0` to `+0` and every NaN to `NAN`, leaving other values
+            /// unchanged.
+            ///
+            /// Canonical values compare and hash like IEEE equality would:
+            /// the two zeros become one key, and so do all NaN. This
+            /// intentionally throws away the sign of zero and the sign and
+            /// payload of NaN.
+            pub fn canonicalize(self) -> FloatOrd<$f> {
+                if self.0.is_nan() {
+                    FloatOrd($f::NAN)
+                } else if self.0.to_bits() == 1 << ($n - 1) {
+                    FloatOrd($f::from_bits(0))
+                } else {
+                    self
+                }
+            }
+            /// Wrap the float with the given raw IEEE 754 bits.
+            ///
+            /// These are the bits used by the float's own `from_bits`. They
+            /// do not sort like the float does; see `from_ord_bits` for
+            /// that.
+            pub fn from_bits(bits: $i) -> FloatOrd<$f> {
+                FloatOrd($f::from_bits(bits))
+            }
+            /// Return the raw IEEE 754 bits of the inner float.
+            ///
+            /// Unlike `to_ord_bits`, comparing these as integers does not
+            /// match the total ordering: negative floats compare in reverse,
+            /// and above every positive one.
+            pub fn to_bits(self) -> $i {
+                self.0.to_bits()
+            }
+            /// Return an unsigned integer key that sorts the same way as
+            /// the float.
+            ///
+            /// Comparing keys with integer comparison gives the same result
+            /// as comparing the `FloatOrd`s, which makes the key suitable for
+            /// radix sorting or database indexes.
+            pub fn to_ord_bits(self) -> $i {
+                self.convert()
+            }
+            /// Rebuild a float from a key returned by `to_ord_bits`.
+            ///
+            /// The round trip is lossless, including for NaN and signed
+            /// zeros. With the `nan-at-top` feature every NaN shares a
+            /// single key, so NaN come back as one positive NaN rather than
+            /// with their original sign and payload.
+            pub fn from_ord_bits(bits: $i) -> FloatOrd<$f> {
+                let bit = 1 << ($n - 1);
+                let u = if bits & bit == 0 {
+                    !bits
+                } else {
+                    bits & !bit
+                };
+                FloatOrd($f::from_bits(u))
+            }
+            /// Return the key from `to_ord_bits` as big-endian bytes.
+            ///
+            /// Comparing two such arrays byte by byte, as `memcmp` does,
+            /// gives the same result as comparing the `FloatOrd`s, so the
+            /// bytes can be written out for an external merge sort.
+            pub fn to_be_key_bytes(self) -> [u8; $n / 8] {
+                self.convert().to_be_bytes()
+            }
+            /// Rebuild a float from bytes returned by `to_be_key_bytes`.
+            pub fn from_be_key_bytes(bytes: [u8; $n / 8]) -> FloatOrd<$f> {
+                Self::from_ord_bits($i::from_be_bytes(bytes))
+            }
+            /// Return `true` if `other` is at most `ulps` steps away in the
+            /// total ordering.
+            ///
+            /// Steps are counted with `next_up`, so `-0` and `+0` are one
+            /// step apart. NaN is never within any distance of anything,
+            /// including itself.
+            pub fn within_ulps(self, other: Self, ulps: u64) -> bool {
+                if self.0.is_nan() || other.0.is_nan() {
+                    return false;
+                }
+                let (a, b) = (self.convert(), other.convert());
+                let distance = if a > b { a - b } else { b - a };
+                distance as u64 <= ulps
+            }
+            /// Return the next value above this one in the total ordering.
+            ///
+            /// This steps from `-0` to `+0`, from the largest finite value
+            /// to +Infinity, and from +Infinity into the positive NaN. The
+            /// largest positive NaN is returned unchanged.
+            pub fn next_up(self) -> FloatOrd<$f> {
+                match self.convert().checked_add(1) {
+                    Some(bits) => Self::from_ord_bits(bits),
+                    None => self,
+                }
+            }
+            /// Return the next value below this one in the total ordering.
+            ///
+            /// This steps from `+0` to `-0`, from the smallest finite value
+            /// to -Infinity, and from -Infinity into the negative NaN. The
+            /// smallest negative NaN is returned unchanged.
+            pub fn next_down(self) -> FloatOrd<$f> {
+                match self.convert().checked_sub(1) {
+                    Some(bits) => Self::from_ord_bits(bits),
+                    None => self,
+                }
+            }
+            #[allow(unknown_lints, unnecessary_transmutes)]
+            fn total_order_key(self) -> $i {
                 let u = unsafe { transmute::<$f, $i>(self.0) };
                 let bit = 1 << ($n - 1);
                 if u & bit == 0 {
@@ -28,6 +451,159 @@ macro_rules! float_ord_impl {
                     !u
                 }
             }
+            #[cfg(not(feature="nan-at-top"))]
+            fn convert(self) -> $i {
+                self.total_order_key()
+            }
+            #[cfg(feature="nan-at-top")]
+            fn convert(self) -> $i {
+                if self.0.is_nan() {
+                    $i::MAX
+                } else {
+                    self.total_order_key()
+                }
+            }
+        }
+        // Unlike the float's own `Debug`, this shows which end of the
+        // ordering a NaN sorts at, and the sign of both zeros.
+        impl fmt::Debug for DebugFloat<$f> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let sign = if self.0.is_sign_negative() { '-' } else { '+' };
+                let bits = self.0.to_bits();
+                if self.0.is_nan() {
+                    write!(f, "NaN{{sign:{}, bits:{:#x}}}", sign, bits)
+                } else if bits << 1 == 0 {
+                    write!(f, "{}0.0", sign)
+                } else {
+                    fmt::Debug::fmt(&self.0, f)
+                }
+            }
+        }
+        impl fmt::Debug for FloatOrd<$f> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_tuple("FloatOrd").field(&DebugFloat(self.0)).finish()
+            }
+        }
+        impl<const N: usize> fmt::Debug for FloatOrd<[$f; N]> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_tuple("FloatOrd").field(&self.0.map(DebugFloat)).finish()
+            }
+        }
+        // Arrays of floats compare lexicographically, using the total
+        // ordering for each element.
+        impl<const N: usize> PartialEq for FloatOrd<[$f; N]> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0.iter().map(FloatOrd::from_ref).eq(other.0.iter().map(FloatOrd::from_ref))
+            }
+        }
+        impl<const N: usize> Eq for FloatOrd<[$f; N]> {}
+        impl<const N: usize> PartialOrd for FloatOrd<[$f; N]> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl<const N: usize> Ord for FloatOrd<[$f; N]> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.iter().map(FloatOrd::from_ref).cmp(other.0.iter().map(FloatOrd::from_ref))
+            }
+        }
+        impl<const N: usize> Hash for FloatOrd<[$f; N]> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                for x in &self.0 {
+                    FloatOrd::from_ref(x).hash(state);
+                }
+            }
+        }
+        impl TotalOrd for $f {
+            fn total_ordering(&self, other: &Self) -> Ordering {
+                FloatOrd(*self).cmp(&FloatOrd(*other))
+            }
+        }
+        impl TotalOrd for FloatOrd<$f> {
+            fn total_ordering(&self, other: &Self) -> Ordering {
+                self.cmp(other)
+            }
+        }
+        // Each step moves to the neighbouring key from `to_ord_bits`. With
+        // `nan-at-top` every NaN shares the key just above +Infinity
+        // instead, so a range ending at NaN takes one step to reach it and
+        // stepping from there returns `NAN`.
+        #[cfg(feature="step")]
+        impl FloatOrd<$f> {
+            #[cfg(not(feature="nan-at-top"))]
+            fn step_key(self) -> $i {
+                self.total_order_key()
+            }
+            #[cfg(not(feature="nan-at-top"))]
+            fn from_step_key(key: $i) -> Option<Self> {
+                Some(Self::from_ord_bits(key))
+            }
+            #[cfg(feature="nan-at-top")]
+            fn step_key(self) -> $i {
+                if self.0.is_nan() {
+                    FloatOrd($f::INFINITY).total_order_key() + 1
+                } else {
+                    self.total_order_key()
+                }
+            }
+            #[cfg(feature="nan-at-top")]
+            fn from_step_key(key: $i) -> Option<Self> {
+                let nan = FloatOrd($f::INFINITY).total_order_key() + 1;
+                if key == nan {
+                    Some(FloatOrd($f::NAN))
+                } else if key > nan || key < FloatOrd($f::NEG_INFINITY).total_order_key() {
+                    None
+                } else {
+                    Some(Self::from_ord_bits(key))
+                }
+            }
+        }
+        #[cfg(feature="step")]
+        impl core::iter::Step for FloatOrd<$f> {
+            fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+                let (start, end) = (start.step_key(), end.step_key());
+                if start > end {
+                    return (0, None);
+                }
+                match usize::try_from(end - start) {
+                    Ok(n) => (n, Some(n)),
+                    Err(_) => (usize::MAX, None),
+                }
+            }
+            fn forward_checked(start: Self, count: usize) -> Option<Self> {
+                let key = $i::try_from(count).ok().and_then(|n| start.step_key().checked_add(n))?;
+                Self::from_step_key(key)
+            }
+            fn backward_checked(start: Self, count: usize) -> Option<Self> {
+                let key = $i::try_from(count).ok().and_then(|n| start.step_key().checked_sub(n))?;
+                Self::from_step_key(key)
+            }
+        }
+        impl Canonicalize for $f {
+            fn canonicalize(self) -> Self {
+                FloatOrd(self).canonicalize().0
+            }
+        }
+        impl sealed::Sealed for $f {}
+        impl Float for $f {
+            fn is_nan(self) -> bool {
+                <$f>::is_nan(self)
+            }
+            fn is_sign_negative(self) -> bool {
+                <$f>::is_sign_negative(self)
+            }
+            fn within_ulps(self, other: Self, ulps: u64) -> bool {
+                FloatOrd(self).within_ulps(FloatOrd(other), ulps)
+            }
+        }
+        impl RadixKey for $f {
+            const BYTES: usize = $n / 8;
+            fn radix_key(self) -> u64 {
+                FloatOrd(self).convert() as u64
+            }
+            fn from_radix_key(key: u64) -> Self {
+                FloatOrd::<$f>::from_ord_bits(key as $i).0
+            }
         }
         impl PartialEq for FloatOrd<$f> {
             fn eq(&self, other: &Self) -> bool {
@@ -35,236 +611,4284 @@ macro_rules! float_ord_impl {
             }
         }
         impl Eq for FloatOrd<$f> {}
+        #[allow(clippy::non_canonical_partial_ord_impl)]
         impl PartialOrd for FloatOrd<$f> {
             fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
                 self.convert().partial_cmp(&other.convert())
             }
         }
         impl Ord for FloatOrd<$f> {
+            #[cfg(any(not(feature="std-total-cmp"), feature="nan-at-top"))]
             fn cmp(&self, other: &Self) -> Ordering {
                 self.convert().cmp(&other.convert())
             }
+            #[cfg(all(feature="std-total-cmp", not(feature="nan-at-top")))]
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.total_cmp(&other.0)
+            }
+        }
+        impl PartialEq<$f> for FloatOrd<$f> {
+            fn eq(&self, other: &$f) -> bool {
+                *self == FloatOrd(*other)
+            }
+        }
+        impl PartialOrd<$f> for FloatOrd<$f> {
+            fn partial_cmp(&self, other: &$f) -> Option<Ordering> {
+                Some(self.cmp(&FloatOrd(*other)))
+            }
         }
         impl Hash for FloatOrd<$f> {
             fn hash<H: Hasher>(&self, state: &mut H) {
                 self.convert().hash(state);
             }
         }
+        impl From<$f> for FloatOrd<$f> {
+            fn from(f: $f) -> Self {
+                FloatOrd(f)
+            }
+        }
+        impl From<FloatOrd<$f>> for $f {
+            fn from(f: FloatOrd<$f>) -> Self {
+                f.0
+            }
+        }
+        impl FromStr for FloatOrd<$f> {
+            type Err = ParseFloatError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $f::from_str(s).map(FloatOrd)
+            }
+        }
+        /// Decode bytes from `to_be_key_bytes`, failing if the slice has
+        /// the wrong length.
+        impl<'a> TryFrom<&'a [u8]> for FloatOrd<$f> {
+            type Error = KeyLengthError;
+            fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+                <[u8; $n / 8]>::try_from(bytes)
+                    .map(Self::from_be_key_bytes)
+                    .map_err(|_| KeyLengthError)
+            }
+        }
     }
 }
 
 float_ord_impl!(f32, u32, 32);
 float_ord_impl!(f64, u64, 64);
 
-impl<T> Default for FloatOrd<T>
-    where T: Default
-{
-    fn default() -> FloatOrd<T> {
-        FloatOrd(T::default())
+impl FloatOrd<f32> {
+    /// Widen to `f64`.
+    ///
+    /// Every `f32`, including subnormals, is exactly representable as an
+    /// `f64`. NaN stay NaN with the same sign, and their payload is moved to
+    /// the top of the wider mantissa, so the result sorts among other
+    /// `f64`s as the NaN did among `f32`s.
+    pub fn to_f64(self) -> f64 {
+        widen(self.0)
+    }
+}
+
+impl FloatOrd<f64> {
+    /// Narrow to `f32`, rounding to the nearest value.
+    ///
+    /// Finite values too large for an `f32` overflow to infinity of the same
+    /// sign, and values too small become subnormal or zero. Use
+    /// `FloatOrd::<f32>::try_from` to reject overflow instead.
+    ///
+    /// NaN stay NaN with the same sign, keeping the top of their payload. If
+    /// that part of the payload is empty, the quiet bit is set so that the
+    /// result is still a NaN.
+    pub fn to_f32(self) -> f32 {
+        if self.0.is_nan() {
+            let bits = self.0.to_bits();
+            let sign = ((bits >> 63) as u32) << 31;
+            let mut payload = ((bits & 0x000f_ffff_ffff_ffff) >> 29) as u32;
+            if payload == 0 {
+                payload = 0x0040_0000;
+            }
+            f32::from_bits(sign | 0x7f80_0000 | payload)
+        } else {
+            self.0 as f32
+        }
+    }
+    /// Convert to an `i64`, rounding toward zero and saturating.
+    ///
+    /// Values below `i64::MIN`, including -Infinity, become `i64::MIN`;
+    /// values above `i64::MAX`, including +Infinity, become `i64::MAX`. NaN
+    /// becomes 0. This is the same as an `as` cast.
+    pub fn to_i64_saturating(self) -> i64 {
+        self.0 as i64
+    }
+    /// Convert to a `u64`, rounding toward zero and saturating.
+    ///
+    /// Negative values, including -Infinity, become 0; values above
+    /// `u64::MAX`, including +Infinity, become `u64::MAX`. NaN becomes 0.
+    /// This is the same as an `as` cast.
+    pub fn to_u64_saturating(self) -> u64 {
+        self.0 as u64
     }
 }
 
-use core::ops::{Deref, Add, Sub, Mul, Div, Rem};
+/// Narrow to `f32`, failing only if a finite value overflows to infinity.
+///
+/// Values that are merely rounded, including ones that lose all precision
+/// and become zero, are converted successfully. Infinities and NaN pass
+/// through unchanged.
+impl TryFrom<FloatOrd<f64>> for FloatOrd<f32> {
+    type Error = OverflowError;
+    fn try_from(f: FloatOrd<f64>) -> Result<Self, Self::Error> {
+        let narrow = f.0 as f32;
+        if narrow.is_infinite() && f.0.is_finite() {
+            Err(OverflowError)
+        } else {
+            Ok(FloatOrd(narrow))
+        }
+    }
+}
 
-impl<T> Deref for FloatOrd<T> {
-    type Target = T;
-    fn deref(&self) -> &Self::Target {
-        &self.0
+// Widen exactly, including the sign and payload of NaN. A plain `as` cast
+// may replace a NaN with the canonical one.
+fn widen(x: f32) -> f64 {
+    if x.is_nan() {
+        let bits = x.to_bits();
+        let sign = ((bits >> 31) as u64) << 63;
+        let payload = ((bits & 0x007f_ffff) as u64) << 29;
+        f64::from_bits(sign | 0x7ff0_0000_0000_0000 | payload)
+    } else {
+        x as f64
     }
 }
 
-macro_rules! float_ord_ops_impl {
-    ($t:ident, $f:ident) => {
-        // FloatOrd<T> + FloatOrd<T> impl
-        impl<T> $t for FloatOrd<T>
-            where T: $t<Output = T>
+/// Compare against an `f64` by promoting the `f32`, according to the total
+/// ordering.
+///
+/// Every `f32` is exactly representable as an `f64`, and NaN are widened
+/// bit for bit, keeping their sign and payload, so the `f32` sorts among
+/// the `f64`s exactly where its value belongs. In particular, this
+/// comparison is consistent with those between two `f32`s or two `f64`s.
+impl PartialEq<FloatOrd<f64>> for FloatOrd<f32> {
+    fn eq(&self, other: &FloatOrd<f64>) -> bool {
+        FloatOrd(widen(self.0)) == *other
+    }
+}
+
+impl PartialOrd<FloatOrd<f64>> for FloatOrd<f32> {
+    fn partial_cmp(&self, other: &FloatOrd<f64>) -> Option<Ordering> {
+        FloatOrd(widen(self.0)).partial_cmp(other)
+    }
+}
+
+/// Compare against an `f32` by promoting it, according to the total
+/// ordering.
+impl PartialEq<FloatOrd<f32>> for FloatOrd<f64> {
+    fn eq(&self, other: &FloatOrd<f32>) -> bool {
+        *self == FloatOrd(widen(other.0))
+    }
+}
+
+impl PartialOrd<FloatOrd<f32>> for FloatOrd<f64> {
+    fn partial_cmp(&self, other: &FloatOrd<f32>) -> Option<Ordering> {
+        self.partial_cmp(&FloatOrd(widen(other.0)))
+    }
+}
+
+#[cfg(feature="half")]
+use half::{bf16, f16};
+#[cfg(feature="half")]
+float_ord_impl!(f16, u16, 16);
+#[cfg(feature="half")]
+float_ord_impl!(bf16, u16, 16);
+
+impl<T> FloatOrd<T> {
+    /// Unwrap the float.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+    /// Return a copy of the float.
+    pub fn get(&self) -> T
+        where T: Copy
+    {
+        self.0
+    }
+    /// View a reference to a float as a reference to a `FloatOrd`.
+    ///
+    /// Floats don't implement `Hash` or `Eq`, so `FloatOrd<T>` can't
+    /// implement `Borrow<T>` in any useful way. Use this instead to look up
+    /// keys in maps without copying:
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use float_ord::FloatOrd;
+    ///
+    /// let mut map = HashMap::new();
+    /// map.insert(FloatOrd(1.5), "a");
+    ///
+    /// let x = 1.5;
+    /// assert_eq!(map.get(FloatOrd::from_ref(&x)), Some(&"a"));
+    /// ```
+    pub fn from_ref(x: &T) -> &FloatOrd<T> {
+        unsafe { transmute(x) }
+    }
+    /// View a mutable reference to a float as a mutable reference to a
+    /// `FloatOrd`.
+    pub fn from_mut(x: &mut T) -> &mut FloatOrd<T> {
+        unsafe { transmute(x) }
+    }
+    /// Wrap the float for comparisons that treat values at most `ulps`
+    /// steps apart as equal.
+    pub fn with_tolerance(self, ulps: u64) -> FloatOrdTolerant<T> {
+        FloatOrdTolerant { value: self.0, ulps }
+    }
+}
+
+// Compare, and hash, a wrapper in the reverse of `FloatOrd`'s ordering.
+macro_rules! reverse_float_ord_impl {
+    ($t:ident) => {
+        impl<T> PartialEq for $t<T>
+            where FloatOrd<T>: Ord
         {
-            type Output = Self;
-            fn $f(self, rhs: Self) -> Self::Output {
-                FloatOrd((self.0).$f(rhs.0))
+            fn eq(&self, other: &Self) -> bool {
+                FloatOrd::from_ref(&self.0) == FloatOrd::from_ref(&other.0)
             }
         }
 
-        // FloatOrd<T> + T impl
-        impl <T> $t<T> for FloatOrd<T> where T: $t<Output = T> {
-            type Output = Self;
-            fn $f(self, rhs: T) -> Self::Output {
-                FloatOrd((self.0).$f(rhs))
-            }
-        }
+        impl<T> Eq for $t<T> where FloatOrd<T>: Ord {}
 
-        // A few impl's of T + FloatOrd<U>
-        impl $t<FloatOrd<f64>> for f64 {
-            type Output = FloatOrd<f64>;
-            fn $f(self, rhs: FloatOrd<f64>) -> Self::Output {
-                FloatOrd((self).$f(rhs.0))
+        impl<T> PartialOrd for $t<T>
+            where FloatOrd<T>: Ord
+        {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
             }
         }
 
-        impl $t<FloatOrd<f32>> for f32 {
-            type Output = FloatOrd<f32>;
-            fn $f(self, rhs: FloatOrd<f32>) -> Self::Output {
-                FloatOrd((self).$f(rhs.0))
+        impl<T> Ord for $t<T>
+            where FloatOrd<T>: Ord
+        {
+            fn cmp(&self, other: &Self) -> Ordering {
+                FloatOrd::from_ref(&other.0).cmp(FloatOrd::from_ref(&self.0))
             }
         }
 
-        impl $t<FloatOrd<f64>> for f32 {
-            type Output = FloatOrd<f64>;
-            fn $f(self, rhs: FloatOrd<f64>) -> Self::Output {
-                FloatOrd((self as f64).$f(rhs.0))
+        impl<T> Hash for $t<T>
+            where FloatOrd<T>: Hash
+        {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                FloatOrd::from_ref(&self.0).hash(state);
             }
         }
     }
 }
 
-float_ord_ops_impl!(Add, add);
-float_ord_ops_impl!(Div, div);
-float_ord_ops_impl!(Rem, rem);
-float_ord_ops_impl!(Mul, mul);
-float_ord_ops_impl!(Sub, sub);
-
-
-
-#[cfg(feature="pdqsort")]
-/// Sort a slice of floats.
+/// A wrapper for floats that uses the reverse of `FloatOrd`'s ordering.
 ///
-/// # Allocation behavior
+/// This is useful for turning a `BinaryHeap` into a min-heap:
 ///
-/// This routine uses a quicksort implementation that does not heap allocate.
+/// ```
+/// use std::collections::BinaryHeap;
+/// use float_ord::MinFloat;
 ///
-/// # Example
+/// let mut heap = BinaryHeap::new();
+/// heap.push(MinFloat(2.0));
+/// heap.push(MinFloat(f64::NAN));
+/// heap.push(MinFloat(-1.0));
+/// assert_eq!(heap.pop().unwrap().0, -1.0);
+/// assert_eq!(heap.pop().unwrap().0, 2.0);
+/// assert!(heap.pop().unwrap().0.is_nan());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MinFloat<T>(pub T);
+
+reverse_float_ord_impl!(MinFloat);
+
+/// A wrapper for optional floats, ordering `None` below every float.
+///
+/// `Some` values follow `FloatOrd`'s ordering, so `Some(NaN)` with the sign
+/// bit clear still sorts at the top, and `None` is never confused with NaN.
 ///
 /// ```
-/// let mut v = [-5.0, 4.0, 1.0, -3.0, 2.0];
+/// use float_ord::OptFloatOrd;
 ///
-/// float_ord::sort(&mut v);
-/// assert!(v == [-5.0, -3.0, 1.0, 2.0, 4.0]);
+/// assert!(OptFloatOrd(None) < OptFloatOrd(Some(-f64::NAN)));
+/// assert!(OptFloatOrd(Some(f64::INFINITY)) < OptFloatOrd(Some(f64::NAN)));
 /// ```
-pub fn sort<T>(v: &mut [T])
-    where FloatOrd<T>: Ord
-{
-    let v_: &mut [FloatOrd<T>] = unsafe { transmute(v) };
-    pdqsort::sort(v_);
-}
+#[derive(Clone, Copy, Debug)]
+pub struct OptFloatOrd<T>(pub Option<T>);
+
+impl<T> OptFloatOrd<T> {
+    fn as_float_ord(&self) -> Option<&FloatOrd<T>> {
+        self.0.as_ref().map(FloatOrd::from_ref)
+    }
+}
+
+impl<T> PartialEq for OptFloatOrd<T>
+    where FloatOrd<T>: Ord
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.as_float_ord() == other.as_float_ord()
+    }
+}
+
+impl<T> Eq for OptFloatOrd<T> where FloatOrd<T>: Ord {}
+
+impl<T> PartialOrd for OptFloatOrd<T>
+    where FloatOrd<T>: Ord
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for OptFloatOrd<T>
+    where FloatOrd<T>: Ord
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_float_ord().cmp(&other.as_float_ord())
+    }
+}
+
+impl<T> Hash for OptFloatOrd<T>
+    where FloatOrd<T>: Hash
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_float_ord().hash(state);
+    }
+}
+
+/// A wrapper for a pair of floats, ordered by the first component and then
+/// the second, using `FloatOrd`'s ordering for each.
+///
+/// This makes coordinate pairs usable as map keys where `-0` and `+0` are
+/// distinct, and NaN in either slot sorts like it would on its own.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use float_ord::FloatOrdPair;
+///
+/// let mut map = BTreeMap::new();
+/// map.insert(FloatOrdPair(0.0, 1.0), "east");
+/// map.insert(FloatOrdPair(-0.0, 1.0), "west");
+/// assert_eq!(map.values().collect::<Vec<_>>(), [&"west", &"east"]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct FloatOrdPair<T>(pub T, pub T);
+
+impl<T> FloatOrdPair<T> {
+    fn as_float_ords(&self) -> (&FloatOrd<T>, &FloatOrd<T>) {
+        (FloatOrd::from_ref(&self.0), FloatOrd::from_ref(&self.1))
+    }
+}
+
+impl<T> PartialEq for FloatOrdPair<T>
+    where FloatOrd<T>: Ord
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.as_float_ords() == other.as_float_ords()
+    }
+}
+
+impl<T> Eq for FloatOrdPair<T> where FloatOrd<T>: Ord {}
+
+impl<T> PartialOrd for FloatOrdPair<T>
+    where FloatOrd<T>: Ord
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for FloatOrdPair<T>
+    where FloatOrd<T>: Ord
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_float_ords().cmp(&other.as_float_ords())
+    }
+}
+
+impl<T> Hash for FloatOrdPair<T>
+    where FloatOrd<T>: Hash
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_float_ords().hash(state);
+    }
+}
+
+/// A closed interval of floats, according to the total ordering.
+///
+/// Because the ordering tells the zeros apart, an interval starting at `+0`
+/// does not contain `-0`. A NaN is only contained in an interval that
+/// reaches a NaN bound, never in a finite or infinite one.
+///
+/// ```
+/// use float_ord::FloatInterval;
+///
+/// let positive = FloatInterval { lo: 0.0, hi: f64::INFINITY };
+/// assert!(positive.contains(&0.0));
+/// assert!(!positive.contains(&-0.0));
+/// assert!(!positive.contains(&f64::NAN));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct FloatInterval<T> {
+    /// The lower bound, which is included.
+    pub lo: T,
+    /// The upper bound, which is included.
+    pub hi: T,
+}
+
+impl<T> FloatInterval<T>
+    where FloatOrd<T>: Ord
+{
+    /// Return `true` if `lo <= x <= hi` in the total ordering.
+    pub fn contains(&self, x: &T) -> bool {
+        let x_ = FloatOrd::from_ref(x);
+        FloatOrd::from_ref(&self.lo) <= x_ && x_ <= FloatOrd::from_ref(&self.hi)
+    }
+}
+
+/// A running minimum and maximum over a stream of floats, according to the
+/// total ordering.
+///
+/// Values are not stored, so this uses constant memory however many are
+/// pushed. A NaN with the sign bit clear becomes the maximum, just as it
+/// would in a sort.
+///
+/// ```
+/// use float_ord::MinMaxAccumulator;
+///
+/// let mut acc = MinMaxAccumulator::new();
+/// for &x in &[2.0, -1.0, 7.5] {
+///     acc.push(x);
+/// }
+/// assert_eq!(acc.min(), Some(-1.0));
+/// assert_eq!(acc.max(), Some(7.5));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MinMaxAccumulator<T> {
+    min: Option<T>,
+    max: Option<T>,
+}
+
+impl<T> MinMaxAccumulator<T>
+    where T: Copy, FloatOrd<T>: Ord
+{
+    /// Create an accumulator that has not seen any values.
+    pub fn new() -> Self {
+        MinMaxAccumulator { min: None, max: None }
+    }
+    /// Add a value to the stream.
+    pub fn push(&mut self, value: T) {
+        self.min = Some(self.min.map_or(value, |m| min(m, value)));
+        self.max = Some(self.max.map_or(value, |m| max(m, value)));
+    }
+    /// Return the smallest value pushed so far, or `None` if there were
+    /// none.
+    pub fn min(&self) -> Option<T> {
+        self.min
+    }
+    /// Return the largest value pushed so far, or `None` if there were
+    /// none.
+    pub fn max(&self) -> Option<T> {
+        self.max
+    }
+}
+
+impl<T> Default for MinMaxAccumulator<T>
+    where T: Copy, FloatOrd<T>: Ord
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature="alloc")]
+/// A vector of floats that is kept sorted by the total ordering.
+///
+/// NaN with the sign bit clear stay at the end, and `-0` stays before `+0`.
+///
+/// ```
+/// use float_ord::SortedFloatVec;
+///
+/// let mut v: SortedFloatVec<f64> = [3.0, f64::NAN, -1.0].iter().cloned().collect();
+/// v.insert(0.5);
+/// assert_eq!(&v.as_slice()[..3], &[-1.0, 0.5, 3.0]);
+/// assert!(v.as_slice()[3].is_nan());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SortedFloatVec<T> {
+    v: Vec<T>,
+}
+
+#[cfg(feature="alloc")]
+impl<T> SortedFloatVec<T>
+    where FloatOrd<T>: Ord
+{
+    /// Create an empty vector.
+    pub fn new() -> Self {
+        SortedFloatVec { v: Vec::new() }
+    }
+    /// Insert a float, keeping the vector sorted.
+    ///
+    /// The new float goes after any that compare equal to it.
+    pub fn insert(&mut self, x: T) {
+        let x_ = FloatOrd::from_ref(&x);
+        let i = as_ord_slice(&self.v).partition_point(|y| y <= x_);
+        self.v.insert(i, x);
+    }
+    /// Return the floats, in sorted order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.v
+    }
+    /// Return the number of floats.
+    pub fn len(&self) -> usize {
+        self.v.len()
+    }
+    /// Return `true` if there are no floats.
+    pub fn is_empty(&self) -> bool {
+        self.v.is_empty()
+    }
+    /// Unwrap the sorted vector.
+    pub fn into_vec(self) -> Vec<T> {
+        self.v
+    }
+}
+
+#[cfg(feature="alloc")]
+impl<T> Default for SortedFloatVec<T>
+    where FloatOrd<T>: Ord
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature="alloc")]
+impl<T> FromIterator<T> for SortedFloatVec<T>
+    where FloatOrd<T>: Ord
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v: Vec<T> = iter.into_iter().collect();
+        sort_stable(&mut v);
+        SortedFloatVec { v }
+    }
+}
+
+#[cfg(feature="alloc")]
+impl<T> Extend<T> for SortedFloatVec<T>
+    where FloatOrd<T>: Ord
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // Sort only the new floats, then merge them into the old ones.
+        let start = self.v.len();
+        self.v.extend(iter);
+        sort_stable(&mut self.v[start..]);
+        let v_ = as_ord_slice(&self.v);
+        if start == 0 || start == v_.len() || v_[start - 1] <= v_[start] {
+            return;
+        }
+        let tail = self.v.split_off(start);
+        let head = core::mem::replace(&mut self.v, Vec::with_capacity(start + tail.len()));
+        let mut head = head.into_iter().peekable();
+        let mut tail = tail.into_iter().peekable();
+        loop {
+            // Equal floats keep their order, the old ones first.
+            let from_head = match (head.peek(), tail.peek()) {
+                (Some(a), Some(b)) => FloatOrd::from_ref(a) <= FloatOrd::from_ref(b),
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let x = if from_head { head.next() } else { tail.next() };
+            self.v.extend(x);
+        }
+    }
+}
+
+/// A wrapper for floats that orders every NaN below `-Infinity`.
+///
+/// Other values follow `FloatOrd`'s ordering. NaN of either sign, and with
+/// any payload, compare equal to each other and hash the same, so sorting
+/// puts all the bad data first.
+///
+/// ```
+/// use float_ord::NanLowest;
+///
+/// let mut v = [NanLowest(1.0), NanLowest(-f64::NAN), NanLowest(-f64::INFINITY),
+///              NanLowest(f64::NAN)];
+/// v.sort();
+/// assert!(v[0].0.is_nan() && v[1].0.is_nan());
+/// assert_eq!(v[2].0, -f64::INFINITY);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct NanLowest<T>(pub T);
+
+macro_rules! nan_lowest_impl {
+    ($f:ident) => {
+        impl NanLowest<$f> {
+            fn key(&self) -> Option<FloatOrd<$f>> {
+                FloatOrd::<$f>::new_finite(self.0)
+            }
+        }
+
+        impl PartialEq for NanLowest<$f> {
+            fn eq(&self, other: &Self) -> bool {
+                self.key() == other.key()
+            }
+        }
+
+        impl Eq for NanLowest<$f> {}
+
+        impl PartialOrd for NanLowest<$f> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for NanLowest<$f> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.key().cmp(&other.key())
+            }
+        }
+
+        impl Hash for NanLowest<$f> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.key().hash(state);
+            }
+        }
+    }
+}
+
+nan_lowest_impl!(f32);
+nan_lowest_impl!(f64);
+#[cfg(feature="half")]
+nan_lowest_impl!(f16);
+#[cfg(feature="half")]
+nan_lowest_impl!(bf16);
+
+/// A wrapper for floats that compares and hashes with IEEE equality.
+///
+/// `-0` and `+0` are the same key, and so are all NaN, matching how many
+/// other systems hash floats. Use `FloatOrd` instead when the zeros or NaN
+/// need to stay apart.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use float_ord::CanonicalFloatOrd;
+///
+/// let mut map = HashMap::new();
+/// map.insert(CanonicalFloatOrd(-0.0), "zero");
+/// assert_eq!(map.get(&CanonicalFloatOrd(0.0)), Some(&"zero"));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct CanonicalFloatOrd<T>(pub T);
+
+macro_rules! canonical_float_ord_impl {
+    ($f:ident) => {
+        impl CanonicalFloatOrd<$f> {
+            fn key(&self) -> FloatOrd<$f> {
+                FloatOrd(self.0).canonicalize()
+            }
+        }
+
+        impl PartialEq for CanonicalFloatOrd<$f> {
+            fn eq(&self, other: &Self) -> bool {
+                self.key() == other.key()
+            }
+        }
+
+        impl Eq for CanonicalFloatOrd<$f> {}
+
+        impl Hash for CanonicalFloatOrd<$f> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.key().hash(state);
+            }
+        }
+    }
+}
+
+canonical_float_ord_impl!(f32);
+canonical_float_ord_impl!(f64);
+#[cfg(feature="half")]
+canonical_float_ord_impl!(f16);
+#[cfg(feature="half")]
+canonical_float_ord_impl!(bf16);
+
+/// A float that compares equal to any other within a number of steps in the
+/// total ordering.
+///
+/// Two values are equal if they are the same `FloatOrd`, or if they are at
+/// most `ulps` steps apart as counted by `FloatOrd::within_ulps`, using the
+/// larger of the two tolerances. Values outside the tolerance band compare
+/// as `FloatOrd` does.
+///
+/// This equality is intentionally not transitive: `a` may equal `b` and `b`
+/// equal `c` while `a` and `c` are too far apart. For that reason the type
+/// only implements `PartialEq` and `PartialOrd`, not `Eq`, `Ord` or `Hash`,
+/// and should not be used as a map key or sorted with `sort`.
+///
+/// ```
+/// use float_ord::FloatOrd;
+///
+/// let a = FloatOrd(1.0f64);
+/// assert!(a.with_tolerance(1) == a.next_up().with_tolerance(1));
+/// assert!(a.with_tolerance(0) != a.next_up().with_tolerance(0));
+/// assert!(a.with_tolerance(1) < FloatOrd(2.0).with_tolerance(1));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct FloatOrdTolerant<T> {
+    /// The float.
+    pub value: T,
+    /// How many steps away another float may be and still compare equal.
+    pub ulps: u64,
+}
+
+macro_rules! float_ord_tolerant_impl {
+    ($f:ident) => {
+        impl PartialEq for FloatOrdTolerant<$f> {
+            fn eq(&self, other: &Self) -> bool {
+                let (a, b) = (FloatOrd(self.value), FloatOrd(other.value));
+                a == b || a.within_ulps(b, cmp::max(self.ulps, other.ulps))
+            }
+        }
+
+        impl PartialOrd for FloatOrdTolerant<$f> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                if self == other {
+                    Some(Ordering::Equal)
+                } else {
+                    Some(FloatOrd(self.value).cmp(&FloatOrd(other.value)))
+                }
+            }
+        }
+    }
+}
+
+float_ord_tolerant_impl!(f32);
+float_ord_tolerant_impl!(f64);
+#[cfg(feature="half")]
+float_ord_tolerant_impl!(f16);
+#[cfg(feature="half")]
+float_ord_tolerant_impl!(bf16);
+
+impl<T> Default for FloatOrd<T>
+    where T: Default
+{
+    fn default() -> FloatOrd<T> {
+        FloatOrd(T::default())
+    }
+}
+
+use core::ops::{Deref, Add, Sub, Mul, Div, Rem, Neg};
+use core::ops::{AddAssign, SubAssign, MulAssign, DivAssign, RemAssign};
+
+impl<T> Deref for FloatOrd<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for FloatOrd<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsMut<T> for FloatOrd<T> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+macro_rules! float_ord_fmt_impl {
+    ($t:ident) => {
+        impl<T> fmt::$t for FloatOrd<T>
+            where T: fmt::$t
+        {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::$t::fmt(&self.0, f)
+            }
+        }
+    }
+}
+
+float_ord_fmt_impl!(Display);
+float_ord_fmt_impl!(LowerExp);
+float_ord_fmt_impl!(UpperExp);
+
+macro_rules! float_ord_ops_impl {
+    ($t:ident, $f:ident) => {
+        // FloatOrd<T> + FloatOrd<T> impl
+        impl<T> $t for FloatOrd<T>
+            where T: $t<Output = T>
+        {
+            type Output = Self;
+            fn $f(self, rhs: Self) -> Self::Output {
+                FloatOrd((self.0).$f(rhs.0))
+            }
+        }
+
+        // FloatOrd<T> + T impl
+        impl <T> $t<T> for FloatOrd<T> where T: $t<Output = T> {
+            type Output = Self;
+            fn $f(self, rhs: T) -> Self::Output {
+                FloatOrd((self.0).$f(rhs))
+            }
+        }
+
+        // A few impl's of T + FloatOrd<U>
+        impl $t<FloatOrd<f64>> for f64 {
+            type Output = FloatOrd<f64>;
+            fn $f(self, rhs: FloatOrd<f64>) -> Self::Output {
+                FloatOrd((self).$f(rhs.0))
+            }
+        }
+
+        impl $t<FloatOrd<f32>> for f32 {
+            type Output = FloatOrd<f32>;
+            fn $f(self, rhs: FloatOrd<f32>) -> Self::Output {
+                FloatOrd((self).$f(rhs.0))
+            }
+        }
+
+        impl $t<FloatOrd<f64>> for f32 {
+            type Output = FloatOrd<f64>;
+            fn $f(self, rhs: FloatOrd<f64>) -> Self::Output {
+                FloatOrd((self as f64).$f(rhs.0))
+            }
+        }
+    }
+}
+
+float_ord_ops_impl!(Add, add);
+float_ord_ops_impl!(Div, div);
+float_ord_ops_impl!(Rem, rem);
+float_ord_ops_impl!(Mul, mul);
+float_ord_ops_impl!(Sub, sub);
+
+macro_rules! float_ord_assign_ops_impl {
+    ($t:ident, $f:ident) => {
+        // FloatOrd<T> += FloatOrd<T> impl
+        impl<T> $t for FloatOrd<T>
+            where T: $t
+        {
+            fn $f(&mut self, rhs: Self) {
+                (self.0).$f(rhs.0)
+            }
+        }
+
+        // FloatOrd<T> += T impl
+        impl<T> $t<T> for FloatOrd<T>
+            where T: $t
+        {
+            fn $f(&mut self, rhs: T) {
+                (self.0).$f(rhs)
+            }
+        }
+    }
+}
+
+float_ord_assign_ops_impl!(AddAssign, add_assign);
+float_ord_assign_ops_impl!(DivAssign, div_assign);
+float_ord_assign_ops_impl!(RemAssign, rem_assign);
+float_ord_assign_ops_impl!(MulAssign, mul_assign);
+float_ord_assign_ops_impl!(SubAssign, sub_assign);
+
+// These fold from `FloatOrd(0.0)` and `FloatOrd(1.0)`. The standard float
+// `Sum` starts from `-0.0` instead, which sorts below `FloatOrd(0.0)`.
+macro_rules! float_ord_iter_impl {
+    ($f:ident) => {
+        impl Sum for FloatOrd<$f> {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(FloatOrd(0.0), |a, b| a + b)
+            }
+        }
+        impl<'a> Sum<&'a FloatOrd<$f>> for FloatOrd<$f> {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.fold(FloatOrd(0.0), |a, b| a + *b)
+            }
+        }
+        impl Product for FloatOrd<$f> {
+            fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(FloatOrd(1.0), |a, b| a * b)
+            }
+        }
+        impl<'a> Product<&'a FloatOrd<$f>> for FloatOrd<$f> {
+            fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.fold(FloatOrd(1.0), |a, b| a * *b)
+            }
+        }
+    }
+}
+
+float_ord_iter_impl!(f32);
+float_ord_iter_impl!(f64);
+
+// Forward a method to the inner float, using the standard library when it is
+// available and the `libm` crate otherwise. The result stays wrapped. The
+// names differ from the float's own methods so that enabling `std` or `libm`
+// never changes what a call reached through `Deref` returns.
+macro_rules! float_ord_forward {
+    ($(#[$doc:meta])* fn $name:ident($($arg:ident: $t:ty),*) => $std:ident, $libm:ident) => {
+        $(#[$doc])*
+        ///
+        /// Requires the `std` or `libm` feature.
+        #[cfg(feature="std")]
+        pub fn $name(self, $($arg: $t),*) -> Self {
+            FloatOrd(self.0.$std($($arg),*))
+        }
+        $(#[$doc])*
+        ///
+        /// Requires the `std` or `libm` feature.
+        #[cfg(all(feature="libm", not(feature="std")))]
+        pub fn $name(self, $($arg: $t),*) -> Self {
+            FloatOrd(libm::$libm(self.0, $($arg as _),*))
+        }
+    }
+}
+
+macro_rules! float_ord_math_impl {
+    ($f:ident, $sqrt:ident, $floor:ident, $ceil:ident, $round:ident, $pow:ident) => {
+        impl FloatOrd<$f> {
+            /// Return the absolute value.
+            pub fn abs(self) -> Self {
+                FloatOrd(self.0.abs())
+            }
+            /// Add, clamping to `MAX` or `MIN` instead of overflowing to
+            /// infinity.
+            ///
+            /// Infinite and NaN inputs are not clamped, and give the same
+            /// result as `+`.
+            pub fn saturating_add(self, rhs: Self) -> Self {
+                self.saturate(rhs, self.0 + rhs.0)
+            }
+            /// Multiply, clamping to `MAX` or `MIN` instead of overflowing to
+            /// infinity.
+            ///
+            /// Infinite and NaN inputs are not clamped, and give the same
+            /// result as `*`.
+            pub fn saturating_mul(self, rhs: Self) -> Self {
+                self.saturate(rhs, self.0 * rhs.0)
+            }
+            /// Divide, returning `None` if finite inputs give an infinite
+            /// or NaN result, such as `1 / 0` or `0 / 0`.
+            ///
+            /// If either input is already infinite or NaN, this returns
+            /// `Some` with the same result as `/`.
+            pub fn checked_div(self, rhs: Self) -> Option<Self> {
+                let result = self.0 / rhs.0;
+                if !result.is_finite() && self.0.is_finite() && rhs.0.is_finite() {
+                    None
+                } else {
+                    Some(FloatOrd(result))
+                }
+            }
+            fn saturate(self, rhs: Self, result: $f) -> Self {
+                if result.is_infinite() && self.0.is_finite() && rhs.0.is_finite() {
+                    FloatOrd(if result > 0.0 { $f::MAX } else { $f::MIN })
+                } else {
+                    FloatOrd(result)
+                }
+            }
+            /// Return the reciprocal, `1 / x`.
+            pub fn recip(self) -> Self {
+                FloatOrd(1.0 / self.0)
+            }
+            float_ord_forward! {
+                /// Return the square root.
+                fn sqrt_wrapped() => sqrt, $sqrt
+            }
+            float_ord_forward! {
+                /// Return the largest integer less than or equal to the
+                /// value.
+                fn floor_wrapped() => floor, $floor
+            }
+            float_ord_forward! {
+                /// Return the smallest integer greater than or equal to the
+                /// value.
+                fn ceil_wrapped() => ceil, $ceil
+            }
+            float_ord_forward! {
+                /// Return the nearest integer, rounding half-way cases away
+                /// from zero.
+                fn round_wrapped() => round, $round
+            }
+            float_ord_forward! {
+                /// Raise to an integer power.
+                fn powi_wrapped(n: i32) => powi, $pow
+            }
+            float_ord_forward! {
+                /// Raise to a floating-point power.
+                fn powf_wrapped(n: $f) => powf, $pow
+            }
+        }
+    }
+}
+
+float_ord_math_impl!(f32, sqrtf, floorf, ceilf, roundf, powf);
+float_ord_math_impl!(f64, sqrt, floor, ceil, round, pow);
+
+#[cfg(feature="num-traits")]
+macro_rules! float_ord_num_impl {
+    ($f:ident) => {
+        // Like float arithmetic, and unlike the total ordering, this treats
+        // both zeros as zero.
+        impl num_traits::Zero for FloatOrd<$f> {
+            fn zero() -> Self {
+                FloatOrd(0.0)
+            }
+            fn is_zero(&self) -> bool {
+                self.0 == 0.0
+            }
+        }
+        impl num_traits::One for FloatOrd<$f> {
+            fn one() -> Self {
+                FloatOrd(1.0)
+            }
+        }
+    }
+}
+
+#[cfg(feature="num-traits")]
+float_ord_num_impl!(f32);
+#[cfg(feature="num-traits")]
+float_ord_num_impl!(f64);
+
+impl<T> Neg for FloatOrd<T>
+    where T: Neg<Output = T>
+{
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        FloatOrd((self.0).neg())
+    }
+}
+
+/// A wrapper for floats that orders them in the reverse of `FloatOrd`'s
+/// ordering.
+///
+/// It compares and hashes exactly like `MinFloat`. Unlike `MinFloat` or
+/// `core::cmp::Reverse<FloatOrd<T>>`, it also dereferences to the float and
+/// supports arithmetic directly, so it can be used as a descending key in a
+/// `BinaryHeap` or `BTreeMap`. Positive NaN, the largest value under
+/// `FloatOrd`, is the minimum here.
+///
+/// ```
+/// use float_ord::RevFloatOrd;
+///
+/// let mut v = [RevFloatOrd(1.0), RevFloatOrd(f64::NAN), RevFloatOrd(3.0)];
+/// v.sort();
+/// assert!(v[0].is_nan());
+/// assert_eq!(*v[1] + 1.0, 4.0);
+/// assert_eq!(v[2].0, 1.0);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(transparent)]
+pub struct RevFloatOrd<T>(pub T);
+
+reverse_float_ord_impl!(RevFloatOrd);
+
+impl<T> Deref for RevFloatOrd<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+macro_rules! rev_float_ord_ops_impl {
+    ($t:ident, $f:ident) => {
+        // RevFloatOrd<T> + RevFloatOrd<T> impl
+        impl<T> $t for RevFloatOrd<T>
+            where T: $t<Output = T>
+        {
+            type Output = Self;
+            fn $f(self, rhs: Self) -> Self::Output {
+                RevFloatOrd((self.0).$f(rhs.0))
+            }
+        }
+
+        // RevFloatOrd<T> + T impl
+        impl<T> $t<T> for RevFloatOrd<T>
+            where T: $t<Output = T>
+        {
+            type Output = Self;
+            fn $f(self, rhs: T) -> Self::Output {
+                RevFloatOrd((self.0).$f(rhs))
+            }
+        }
+    }
+}
+
+rev_float_ord_ops_impl!(Add, add);
+rev_float_ord_ops_impl!(Div, div);
+rev_float_ord_ops_impl!(Rem, rem);
+rev_float_ord_ops_impl!(Mul, mul);
+rev_float_ord_ops_impl!(Sub, sub);
+
+impl<T> Neg for RevFloatOrd<T>
+    where T: Neg<Output = T>
+{
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        RevFloatOrd((self.0).neg())
+    }
+}
+
+#[cfg(feature="serde")]
+impl<T> serde::Serialize for FloatOrd<T>
+    where T: serde::Serialize
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature="serde")]
+impl<'de, T> serde::Deserialize<'de> for FloatOrd<T>
+    where T: serde::Deserialize<'de>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(FloatOrd)
+    }
+}
+
+/// Return the value halfway between `a` and `b` in the total ordering,
+/// counting representable values rather than distance on the number line.
+///
+/// This is a bisection helper: unlike `(a + b) / 2`, repeatedly taking the
+/// midpoint of the remaining interval reaches two adjacent values within 64
+/// steps, no matter how far apart `a` and `b` start. When there is no value
+/// strictly between the two, the smaller one is returned.
+///
+/// # Example
+///
+/// ```
+/// use float_ord::FloatOrd;
+///
+/// let mid = float_ord::midpoint_bits(FloatOrd(1.0f64), FloatOrd(4.0));
+/// assert_eq!(mid, FloatOrd(2.0));
+/// let mid = float_ord::midpoint_bits(FloatOrd(-1.0f64), FloatOrd(1.0));
+/// assert!(mid.0 == 0.0 && mid.0.is_sign_negative());
+/// ```
+pub fn midpoint_bits<T>(a: FloatOrd<T>, b: FloatOrd<T>) -> FloatOrd<T>
+    where T: RadixKey
+{
+    let (a, b) = (a.0.radix_key(), b.0.radix_key());
+    FloatOrd(T::from_radix_key((a & b) + ((a ^ b) >> 1)))
+}
+
+/// Iterate over representable floats from `start` up to, but not including,
+/// `end`, yielding every `step`th one.
+///
+/// The iterator walks keys from `to_ord_bits`, so each step moves to the
+/// next value in the total ordering, crossing from `-0` to `+0` and on
+/// through subnormals like any other pair of neighbours. If `start` does not
+/// sort below `end`, nothing is yielded.
+///
+/// # Panics
+///
+/// Panics if `step` is zero.
+///
+/// # Example
+///
+/// ```
+/// use float_ord::FloatOrd;
+///
+/// let one = FloatOrd(1.0f64);
+/// assert_eq!(float_ord::ulp_range(one, one.next_up(), 1).count(), 1);
+/// assert_eq!(float_ord::ulp_range(FloatOrd(-0.0f64), FloatOrd(0.0), 1).count(), 1);
+/// ```
+pub fn ulp_range<T>(start: FloatOrd<T>, end: FloatOrd<T>, step: u64) -> UlpRange<T>
+    where T: RadixKey
+{
+    assert!(step != 0, "ulp_range step must be non-zero");
+    UlpRange {
+        next: start.0.radix_key(),
+        end: end.0.radix_key(),
+        step,
+        marker: PhantomData,
+    }
+}
+
+/// An iterator over representable floats, returned by `ulp_range`.
+#[derive(Clone, Debug)]
+pub struct UlpRange<T> {
+    next: u64,
+    end: u64,
+    step: u64,
+    marker: PhantomData<T>,
+}
+
+impl<T> Iterator for UlpRange<T>
+    where T: RadixKey
+{
+    type Item = FloatOrd<T>;
+    fn next(&mut self) -> Option<FloatOrd<T>> {
+        if self.next >= self.end {
+            return None;
+        }
+        let key = self.next;
+        self.next = key.checked_add(self.step).unwrap_or(self.end);
+        Some(FloatOrd(T::from_radix_key(key)))
+    }
+}
+
+macro_rules! const_cmp_impl {
+    ($name:ident, $f:ident, $i:ident, $n:expr) => {
+        /// Compare two floats according to the total ordering, in a const
+        /// context.
+        ///
+        /// This gives the same result as comparing the `FloatOrd`s, and can
+        /// be used to build sorted lookup tables at compile time.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use std::cmp::Ordering;
+        ///
+        #[doc = concat!("const LESS: Ordering = float_ord::", stringify!($name),
+                        "(-0.0, 0.0);")]
+        /// assert_eq!(LESS, Ordering::Less);
+        /// ```
+        pub const fn $name(a: $f, b: $f) -> Ordering {
+            const fn key(x: $f) -> $i {
+                let u = x.to_bits();
+                let bit = 1 << ($n - 1);
+                if cfg!(feature="nan-at-top") && x.is_nan() {
+                    $i::MAX
+                } else if u & bit == 0 {
+                    u | bit
+                } else {
+                    !u
+                }
+            }
+            let (a, b) = (key(a), key(b));
+            if a < b {
+                Ordering::Less
+            } else if a > b {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        }
+    }
+}
+
+const_cmp_impl!(const_cmp_f32, f32, u32, 32);
+const_cmp_impl!(const_cmp_f64, f64, u64, 64);
+
+/// Canonicalize every float in a slice, in place.
+///
+/// Each `-0` becomes `+0` and each NaN becomes `NAN`, as with
+/// `FloatOrd::canonicalize`, so collecting the result into a
+/// `HashSet<FloatOrd<T>>` keeps a single zero and a single NaN.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-0.0, 0.0, -f64::NAN];
+///
+/// float_ord::canonicalize_slice(&mut v);
+/// assert!(v[0].is_sign_positive() && v[2].is_sign_positive());
+/// ```
+pub fn canonicalize_slice<T>(v: &mut [T])
+    where T: Canonicalize
+{
+    for x in v {
+        *x = x.canonicalize();
+    }
+}
+
+/// Restrict every float in a slice to the interval `[lo, hi]`, according to
+/// the total ordering.
+///
+/// Elements below `lo` are replaced with `lo`, and elements above `hi` with
+/// `hi`. Positive NaN sorts above every number, so it is replaced with `hi`.
+///
+/// # Panics
+///
+/// Panics if `lo > hi`.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-3.0, 0.5, f64::NAN, 7.0];
+///
+/// float_ord::clamp_slice(&mut v, 0.0, 1.0);
+/// assert_eq!(v, [0.0, 0.5, 1.0, 1.0]);
+/// ```
+pub fn clamp_slice<T>(v: &mut [T], lo: T, hi: T)
+    where T: Copy,
+          FloatOrd<T>: Ord
+{
+    let (lo, hi) = (FloatOrd(lo), FloatOrd(hi));
+    for x in as_ord_slice_mut(v) {
+        *x = Ord::clamp(*x, lo, hi);
+    }
+}
+
+/// Return the smaller of two floats, according to the total ordering.
+///
+/// Unlike `f64::min`, a NaN argument is not ignored, and `-0` is smaller
+/// than `+0`.
+///
+/// # Example
+///
+/// ```
+/// assert!(float_ord::min(-0.0f64, 0.0).is_sign_negative());
+/// assert_eq!(float_ord::min(1.0, -f64::INFINITY), -f64::INFINITY);
+/// ```
+pub fn min<T>(a: T, b: T) -> T
+    where FloatOrd<T>: Ord
+{
+    cmp::min(FloatOrd(a), FloatOrd(b)).0
+}
+
+/// Return the larger of two floats, according to the total ordering.
+///
+/// Unlike `f64::max`, a NaN argument is not ignored, and `+0` is larger
+/// than `-0`.
+///
+/// # Example
+///
+/// ```
+/// assert!(!float_ord::max(-0.0f64, 0.0).is_sign_negative());
+/// assert!(float_ord::max(1.0, f64::NAN).is_nan());
+/// ```
+pub fn max<T>(a: T, b: T) -> T
+    where FloatOrd<T>: Ord
+{
+    cmp::max(FloatOrd(a), FloatOrd(b)).0
+}
+
+/// Return the element of a slice with the smallest float key, according to
+/// the total ordering.
+///
+/// If several elements share the smallest key, the first is returned. An
+/// empty slice gives `None`.
+///
+/// # Example
+///
+/// ```
+/// let v = [("a", 2.0), ("b", -0.0), ("c", 0.0)];
+///
+/// assert_eq!(float_ord::min_by_key(&v, |x| x.1), Some(&("b", -0.0)));
+/// ```
+pub fn min_by_key<T, K, F>(v: &[T], mut f: F) -> Option<&T>
+    where F: FnMut(&T) -> K,
+          FloatOrd<K>: Ord
+{
+    v.iter().min_by_key(|x| FloatOrd(f(x)))
+}
+
+/// Return the element of a slice with the largest float key, according to
+/// the total ordering.
+///
+/// A NaN key with the sign bit clear counts as the largest. If several
+/// elements share the largest key, the last is returned. An empty slice
+/// gives `None`.
+///
+/// # Example
+///
+/// ```
+/// let v = [("a", 2.0), ("b", f64::NAN), ("c", 0.0)];
+///
+/// assert_eq!(float_ord::max_by_key(&v, |x| x.1).unwrap().0, "b");
+/// ```
+pub fn max_by_key<T, K, F>(v: &[T], mut f: F) -> Option<&T>
+    where F: FnMut(&T) -> K,
+          FloatOrd<K>: Ord
+{
+    v.iter().max_by_key(|x| FloatOrd(f(x)))
+}
+
+/// Compare two floats according to the total ordering.
+///
+/// Together with `FloatOrderingExt::then_float`, this chains comparisons of
+/// several float fields.
+///
+/// # Example
+///
+/// ```
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(float_ord::compare(&-0.0, &0.0), Ordering::Less);
+/// assert_eq!(float_ord::compare(&f64::NAN, &f64::NAN), Ordering::Equal);
+/// ```
+pub fn compare<T>(a: &T, b: &T) -> Ordering
+    where FloatOrd<T>: Ord
+{
+    FloatOrd::from_ref(a).cmp(FloatOrd::from_ref(b))
+}
+
+/// Compare two `FloatOrd`s by reference.
+///
+/// This is the same as `a.cmp(b)`, in a form that can be passed directly to
+/// functions like `sort_by` on a slice of references. References to
+/// `FloatOrd` already implement `Ord` through the standard library, so they
+/// can be stored in a `BinaryHeap` or `BTreeSet` without copying.
+///
+/// # Example
+///
+/// ```
+/// use float_ord::FloatOrd;
+///
+/// let (a, b) = (FloatOrd(-0.0f64), FloatOrd(0.0));
+/// let mut v = vec![&b, &a];
+/// v.sort_by(|x, y| float_ord::cmp_ref(x, y));
+/// assert!(v[0].0.is_sign_negative());
+/// ```
+pub fn cmp_ref<T>(a: &FloatOrd<T>, b: &FloatOrd<T>) -> Ordering
+    where FloatOrd<T>: Ord
+{
+    a.cmp(b)
+}
+
+#[cfg(feature="num-complex")]
+/// Compare two complex numbers by magnitude, according to the total
+/// ordering.
+///
+/// This compares the squared magnitudes `re * re + im * im`, which orders
+/// the same way as the magnitudes without taking a square root. A complex
+/// number with a NaN component has a NaN magnitude, which always sorts at
+/// the top, whatever the sign of the NaN.
+///
+/// The squares are not rescaled, so they overflow to infinity once a
+/// magnitude passes about `1.3e154` for `f64` (`1.8e19` for `f32`), and
+/// underflow to zero below about `2e-162` (`4e-23` for `f32`). Numbers past
+/// either limit compare equal to each other.
+///
+/// # Example
+///
+/// ```
+/// extern crate num_complex;
+/// extern crate float_ord;
+///
+/// use num_complex::Complex;
+///
+/// # fn main() {
+/// let mut v = [Complex::new(3.0, 4.0), Complex::new(f64::NAN, 0.0), Complex::new(0.0, -1.0)];
+/// v.sort_by(float_ord::cmp_by_magnitude);
+/// assert_eq!(v[0], Complex::new(0.0, -1.0));
+/// assert_eq!(v[1], Complex::new(3.0, 4.0));
+/// # }
+/// ```
+pub fn cmp_by_magnitude<T>(a: &num_complex::Complex<T>, b: &num_complex::Complex<T>) -> Ordering
+    where T: num_traits::float::FloatCore,
+          FloatOrd<T>: Ord
+{
+    let norm_sqr = |z: &num_complex::Complex<T>| {
+        let n = z.re * z.re + z.im * z.im;
+        FloatOrd(if n.is_nan() { T::nan() } else { n })
+    };
+    norm_sqr(a).cmp(&norm_sqr(b))
+}
+
+/// Compare two float slices lexicographically, according to the total
+/// ordering.
+///
+/// If one slice is a prefix of the other, the shorter one orders first. This
+/// can be passed straight to `sort_by` to sort a `Vec<Vec<f64>>`.
+///
+/// # Example
+///
+/// ```
+/// let mut v = vec![vec![1.0, 0.0], vec![1.0], vec![-0.0, 5.0]];
+/// v.sort_by(|a, b| float_ord::cmp_slices(a, b));
+/// assert_eq!(v, [vec![-0.0, 5.0], vec![1.0], vec![1.0, 0.0]]);
+/// ```
+pub fn cmp_slices<T>(a: &[T], b: &[T]) -> Ordering
+    where FloatOrd<T>: Ord
+{
+    as_ord_slice(a).cmp(as_ord_slice(b))
+}
+
+/// Return inclusive bounds from `lo` to `hi`, for range queries on maps and
+/// sets keyed by `FloatOrd`.
+///
+/// The bounds follow the total ordering, so `-0` and `+0` are different
+/// bounds, and where a NaN bound falls depends on its sign. NaN with the
+/// sign bit clear sort above +Infinity, so a range up to `f64::NAN` covers
+/// the positive NaN keys, but not the negative ones. NaN with the sign bit
+/// set sort below -Infinity, so they are only in ranges that start at a
+/// negative NaN, such as `range_bounds(-f64::NAN, f64::INFINITY)`. `f64::NAN`
+/// itself is positive, while `-f64::NAN` is negative.
+///
+/// With the `nan-at-top` feature, every NaN sorts above +Infinity whatever
+/// its sign, so any NaN bound means "up to and including all NaNs" as `hi`,
+/// and "only the NaNs" as `lo`.
+///
+/// As with any range, `BTreeMap::range` panics if `lo` sorts after `hi`.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeSet;
+/// use float_ord::FloatOrd;
+///
+/// let set: BTreeSet<_> = [-1.0, 0.0, 2.0, f64::NAN].iter().map(|&x| FloatOrd(x)).collect();
+/// let r = set.range(float_ord::range_bounds(0.0, f64::INFINITY));
+/// assert_eq!(r.collect::<Vec<_>>(), [&FloatOrd(0.0), &FloatOrd(2.0)]);
+/// ```
+pub fn range_bounds<T>(lo: T, hi: T) -> (Bound<FloatOrd<T>>, Bound<FloatOrd<T>>) {
+    (Bound::Included(FloatOrd(lo)), Bound::Included(FloatOrd(hi)))
+}
+
+/// View a slice of floats as a slice of `FloatOrd`s, without copying.
+///
+/// # Example
+///
+/// ```
+/// use float_ord::FloatOrd;
+///
+/// let v = [3.0, -0.0, 0.0];
+/// assert!(float_ord::as_ord_slice(&v).iter().max() == Some(&FloatOrd(3.0)));
+/// ```
+pub fn as_ord_slice<T>(v: &[T]) -> &[FloatOrd<T>] {
+    unsafe { transmute(v) }
+}
+
+/// View a mutable slice of floats as a mutable slice of `FloatOrd`s, without
+/// copying.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [3.0f64, -0.0, 0.0];
+/// float_ord::as_ord_slice_mut(&mut v).sort_unstable();
+/// assert!(v[0].is_sign_negative() && v[2] == 3.0);
+/// ```
+pub fn as_ord_slice_mut<T>(v: &mut [T]) -> &mut [FloatOrd<T>] {
+    unsafe { transmute(v) }
+}
+
+/// View a slice of `FloatOrd`s as a slice of floats, without copying.
+pub fn from_ord_slice<T>(v: &[FloatOrd<T>]) -> &[T] {
+    unsafe { transmute(v) }
+}
+
+/// View a mutable slice of `FloatOrd`s as a mutable slice of floats, without
+/// copying.
+pub fn from_ord_slice_mut<T>(v: &mut [FloatOrd<T>]) -> &mut [T] {
+    unsafe { transmute(v) }
+}
+
+#[cfg(feature="pdqsort")]
+/// Sort a slice of floats.
+///
+/// # Allocation behavior
+///
+/// This routine uses a quicksort implementation that does not heap allocate.
+/// Without the `pdqsort` feature, the `alloc` feature provides a merge sort
+/// here instead.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-5.0, 4.0, 1.0, -3.0, 2.0];
+///
+/// float_ord::sort(&mut v);
+/// assert!(v == [-5.0, -3.0, 1.0, 2.0, 4.0]);
+/// ```
+pub fn sort<T>(v: &mut [T])
+    where FloatOrd<T>: Ord
+{
+    sort_with_threshold(v, INSERTION_THRESHOLD);
+}
+
+// Slices this short are insertion sorted by `sort`. pdqsort switches to
+// insertion sort at the same length internally.
+#[cfg(feature="pdqsort")]
+const INSERTION_THRESHOLD: usize = 20;
+
+#[cfg(feature="pdqsort")]
+/// Sort a slice of floats, using insertion sort if it is shorter than
+/// `insertion_threshold`.
+///
+/// Below the threshold this skips pdqsort's setup, which can be faster for
+/// small slices. `sort` uses a threshold of 20.
+///
+/// # Allocation behavior
+///
+/// This routine does not heap allocate.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-5.0, 4.0, 1.0, -3.0, 2.0];
+///
+/// float_ord::sort_with_threshold(&mut v, 8);
+/// assert!(v == [-5.0, -3.0, 1.0, 2.0, 4.0]);
+/// ```
+pub fn sort_with_threshold<T>(v: &mut [T], insertion_threshold: usize)
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice_mut(v);
+    if v_.len() < insertion_threshold {
+        for i in 1..v_.len() {
+            let mut j = i;
+            while j > 0 && v_[j - 1] > v_[j] {
+                v_.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    } else {
+        pdqsort::sort(v_);
+    }
+}
+
+#[cfg(all(feature="alloc", not(feature="pdqsort")))]
+/// Sort a slice of floats.
+///
+/// # Allocation behavior
+///
+/// Without the `pdqsort` feature, this routine uses the standard library's
+/// merge sort, which allocates a scratch buffer. Enable `pdqsort` for a
+/// quicksort that does not heap allocate.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-5.0, 4.0, 1.0, -3.0, 2.0];
+///
+/// float_ord::sort(&mut v);
+/// assert!(v == [-5.0, -3.0, 1.0, 2.0, 4.0]);
+/// ```
+pub fn sort<T>(v: &mut [T])
+    where FloatOrd<T>: Ord
+{
+    v.sort_by(|a, b| FloatOrd::from_ref(a).cmp(FloatOrd::from_ref(b)));
+}
+
+#[cfg(any(feature="pdqsort", feature="alloc"))]
+/// Sort a slice of floats in place, and return a view of it in sorted order.
+///
+/// The slice is sorted once, as by `sort`. The returned view borrows it, so
+/// it can't be modified while the view is alive.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [2.0, f64::NAN, -1.0];
+///
+/// let view = float_ord::sorted_view(&mut v);
+/// assert_eq!(view.get(0), Some(&-1.0));
+/// assert!(view.iter().last().unwrap().is_nan());
+/// ```
+pub fn sorted_view<T>(v: &mut [T]) -> SortedView<'_, T>
+    where FloatOrd<T>: Ord
+{
+    sort(v);
+    SortedView { v }
+}
+
+#[cfg(any(feature="pdqsort", feature="alloc"))]
+/// A slice of floats known to be sorted, returned by `sorted_view`.
+#[derive(Clone, Copy, Debug)]
+pub struct SortedView<'a, T: 'a> {
+    v: &'a [T],
+}
+
+#[cfg(any(feature="pdqsort", feature="alloc"))]
+impl<'a, T> SortedView<'a, T> {
+    /// Return the number of floats.
+    pub fn len(&self) -> usize {
+        self.v.len()
+    }
+    /// Return `true` if there are no floats.
+    pub fn is_empty(&self) -> bool {
+        self.v.is_empty()
+    }
+    /// Return the float at position `i` in sorted order, or `None` if `i`
+    /// is out of bounds.
+    pub fn get(&self, i: usize) -> Option<&'a T> {
+        self.v.get(i)
+    }
+    /// Iterate over the floats in sorted order.
+    pub fn iter(&self) -> slice::Iter<'a, T> {
+        self.v.iter()
+    }
+    /// View the sorted floats as a slice.
+    pub fn as_slice(&self) -> &'a [T] {
+        self.v
+    }
+}
+
+#[cfg(any(feature="pdqsort", feature="alloc"))]
+impl<'a, T> IntoIterator for SortedView<'a, T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.v.iter()
+    }
+}
+
+#[cfg(any(feature="pdqsort", feature="alloc"))]
+/// Sort a slice of floats, putting every NaN at the end.
+///
+/// Numbers are ordered as by `sort`, including `-0` before `+0`, but NaN
+/// with the sign bit set are moved to the end along with the others,
+/// instead of sorting first. They follow the positive NaN.
+///
+/// This shares its name with the unstable `[f64]::sort_floats`, but not
+/// quite its ordering: that method follows `total_cmp`, which, like `sort`,
+/// puts negative NaN first.
+///
+/// Only slices of the float types themselves are accepted, since an array
+/// that merely contains a NaN is not a NaN:
+///
+/// ```compile_fail
+/// float_ord::sort_floats(&mut [[1.0f64, f64::NAN], [0.0, 2.0]]);
+/// ```
+///
+/// # Allocation behavior
+///
+/// This routine allocates exactly when `sort` does.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [f64::NAN, 2.0, -f64::NAN, -1.0];
+///
+/// float_ord::sort_floats(&mut v);
+/// assert_eq!(&v[..2], &[-1.0, 2.0]);
+/// assert!(v[2].is_nan() && v[3].is_nan());
+/// ```
+pub fn sort_floats<T>(v: &mut [T])
+    where T: Float,
+          FloatOrd<T>: Ord
+{
+    sort(v);
+    let negative_nan = v.iter().take_while(|x| x.is_nan() && x.is_sign_negative()).count();
+    v.rotate_left(negative_nan);
+}
+
+#[cfg(feature="pdqsort")]
+/// Sort a slice of floats, largest first.
+///
+/// This is the exact reverse of `sort`: NaN with the sign bit clear comes
+/// first, followed by +Infinity, and `+0` comes before `-0`.
+///
+/// # Allocation behavior
+///
+/// This routine uses a quicksort implementation that does not heap allocate.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-5.0, 4.0, 1.0, -3.0, 2.0];
+///
+/// float_ord::sort_descending(&mut v);
+/// assert!(v == [4.0, 2.0, 1.0, -3.0, -5.0]);
+/// ```
+pub fn sort_descending<T>(v: &mut [T])
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice_mut(v);
+    pdqsort::sort_by(v_, |a, b| b.cmp(a));
+}
+
+#[cfg(feature="pdqsort")]
+/// Sort a slice by a float key.
+///
+/// Each key is wrapped in `FloatOrd`, so NaN keys end up at either end of the
+/// slice just like `sort` would put them.
+///
+/// # Allocation behavior
+///
+/// This routine uses a quicksort implementation that does not heap allocate.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [(2.0, 'a'), (-1.0, 'b'), (0.5, 'c')];
+///
+/// float_ord::sort_by_key(&mut v, |x| x.0);
+/// assert!(v == [(-1.0, 'b'), (0.5, 'c'), (2.0, 'a')]);
+/// ```
+pub fn sort_by_key<T, F, K>(v: &mut [T], mut f: F)
+    where F: FnMut(&T) -> K,
+          FloatOrd<K>: Ord
+{
+    pdqsort::sort_by_key(v, |x| FloatOrd(f(x)));
+}
+
+#[cfg(feature="pdqsort")]
+/// Sort a slice of floats, breaking ties between equal floats with
+/// `tie_break`.
+///
+/// `tie_break` is only called for pairs that are equal in the total
+/// ordering, so it cannot move NaN or any other value out of its place. With
+/// the default ordering equal floats have the same bits; with the
+/// `nan-at-top` feature, it can order NaN by payload.
+///
+/// # Allocation behavior
+///
+/// This routine uses a quicksort implementation that does not heap allocate.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [2.0, f64::NAN, -1.0];
+///
+/// float_ord::sort_by_then(&mut v, |a, b| a.to_bits().cmp(&b.to_bits()));
+/// assert_eq!(&v[..2], &[-1.0, 2.0]);
+/// assert!(v[2].is_nan());
+/// ```
+pub fn sort_by_then<T, F>(v: &mut [T], mut tie_break: F)
+    where FloatOrd<T>: Ord,
+          F: FnMut(&T, &T) -> Ordering
+{
+    pdqsort::sort_by(v, |a, b| {
+        FloatOrd::from_ref(a).cmp(FloatOrd::from_ref(b)).then_with(|| tie_break(a, b))
+    });
+}
+
+#[cfg(feature="rayon")]
+/// Sort a slice of floats in parallel.
+///
+/// The result is identical to `sort`.
+///
+/// # Allocation behavior
+///
+/// This routine uses rayon's parallel quicksort, which does not heap
+/// allocate beyond rayon's own thread pool.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-5.0, 4.0, 1.0, -3.0, 2.0];
+///
+/// float_ord::par_sort(&mut v);
+/// assert!(v == [-5.0, -3.0, 1.0, 2.0, 4.0]);
+/// ```
+pub fn par_sort<T>(v: &mut [T])
+    where T: Send,
+          FloatOrd<T>: Ord
+{
+    use rayon::slice::ParallelSliceMut;
+    let v_ = as_ord_slice_mut(v);
+    v_.par_sort_unstable();
+}
+
+#[cfg(feature="alloc")]
+/// Sort a slice of floats, preserving the order of equal elements.
+///
+/// # Allocation behavior
+///
+/// This routine uses a merge sort that allocates a scratch buffer.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-5.0, 4.0, 1.0, -3.0, 2.0];
+///
+/// float_ord::sort_stable(&mut v);
+/// assert!(v == [-5.0, -3.0, 1.0, 2.0, 4.0]);
+/// ```
+pub fn sort_stable<T>(v: &mut [T])
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice_mut(v);
+    v_.sort();
+}
+
+#[cfg(feature="alloc")]
+/// Sort a slice by a float key, preserving the order of elements with equal
+/// keys.
+///
+/// # Allocation behavior
+///
+/// This routine uses a merge sort that allocates a scratch buffer.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [(1.0, 'a'), (-1.0, 'b'), (1.0, 'c')];
+///
+/// float_ord::sort_stable_by_key(&mut v, |x| x.0);
+/// assert!(v == [(-1.0, 'b'), (1.0, 'a'), (1.0, 'c')]);
+/// ```
+pub fn sort_stable_by_key<T, F, K>(v: &mut [T], mut f: F)
+    where F: FnMut(&T) -> K,
+          FloatOrd<K>: Ord
+{
+    v.sort_by_key(|x| FloatOrd(f(x)));
+}
+
+#[cfg(feature="alloc")]
+/// Sort a slice by a float key, calling the key function only once per
+/// element.
+///
+/// This is the float equivalent of `slice::sort_by_cached_key`, and is
+/// faster than `sort_stable_by_key` when the key function is expensive.
+/// Elements with equal keys keep their original order.
+///
+/// # Allocation behavior
+///
+/// This routine allocates a vector holding every key, as
+/// `slice::sort_by_cached_key` does.
+///
+/// # Example
+///
+/// ```
+/// let mut v = ["1.5", "-2", "nan", "0"];
+///
+/// float_ord::sort_by_cached_key(&mut v, |s| s.parse::<f64>().unwrap());
+/// assert!(v == ["-2", "0", "1.5", "nan"]);
+/// ```
+pub fn sort_by_cached_key<T, F, K>(v: &mut [T], mut f: F)
+    where F: FnMut(&T) -> K,
+          FloatOrd<K>: Ord
+{
+    v.sort_by_cached_key(|x| FloatOrd(f(x)));
+}
+
+#[cfg(feature="alloc")]
+/// Return the permutation of indices that would sort a slice of floats.
+///
+/// The slice itself is left untouched. Indices of equal elements stay in
+/// ascending order.
+///
+/// # Allocation behavior
+///
+/// This routine allocates the returned vector, and uses a merge sort that
+/// allocates a scratch buffer.
+///
+/// # Example
+///
+/// ```
+/// let v = [3.0, -1.0, f64::NAN, 0.5];
+///
+/// assert_eq!(float_ord::argsort(&v), [1, 3, 0, 2]);
+/// ```
+pub fn argsort<T>(v: &[T]) -> Vec<usize>
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice(v);
+    let mut indices: Vec<usize> = (0..v.len()).collect();
+    indices.sort_by_key(|&i| &v_[i]);
+    indices
+}
+
+#[cfg(feature="alloc")]
+/// Return the `k` largest floats in a slice, largest first.
+///
+/// NaN with the sign bit clear counts as larger than any number. If `k` is
+/// at least the length of the slice, every element is returned.
+///
+/// # Allocation behavior
+///
+/// This routine keeps a binary heap of at most `k` elements, so it takes
+/// O(n log k) time instead of sorting the whole slice.
+///
+/// # Example
+///
+/// ```
+/// let v = [3.0, -1.0, 7.0, 0.5, 7.0];
+///
+/// assert_eq!(float_ord::top_k(&v, 3), [7.0, 7.0, 3.0]);
+/// ```
+pub fn top_k<T>(v: &[T], k: usize) -> Vec<T>
+    where T: Copy,
+          FloatOrd<T>: Ord
+{
+    let mut heap = BinaryHeap::with_capacity(cmp::min(k, v.len()));
+    for &x in v {
+        if heap.len() < k {
+            heap.push(MinFloat(x));
+        } else if let Some(mut smallest) = heap.peek_mut() {
+            if FloatOrd(x) > FloatOrd(smallest.0) {
+                *smallest = MinFloat(x);
+            }
+        }
+    }
+    heap.into_sorted_vec().into_iter().map(|x| x.0).collect()
+}
+
+#[cfg(feature="alloc")]
+/// Sort a slice of floats using an LSD radix sort.
+///
+/// The result is identical to `sort`, but takes linear time, which is much
+/// faster for large slices.
+///
+/// # Allocation behavior
+///
+/// This routine allocates two buffers, each holding a key and a copy of every
+/// element.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-5.0, 4.0, 1.0, -3.0, 2.0];
+///
+/// float_ord::radix_sort(&mut v);
+/// assert!(v == [-5.0, -3.0, 1.0, 2.0, 4.0]);
+/// ```
+pub fn radix_sort<T>(v: &mut [T])
+    where T: RadixKey
+{
+    let mut src: Vec<(u64, T)> = v.iter().map(|&x| (x.radix_key(), x)).collect();
+    let mut dst = src.clone();
+    for pass in 0..T::BYTES {
+        let shift = pass * 8;
+        let mut counts = [0usize; 256];
+        for &(key, _) in &src {
+            counts[(key >> shift) as usize & 0xff] += 1;
+        }
+        // Every element has the same byte here, so this pass is a no-op.
+        if counts.contains(&src.len()) {
+            continue;
+        }
+        let mut offset = 0;
+        for count in counts.iter_mut() {
+            let c = *count;
+            *count = offset;
+            offset += c;
+        }
+        for &item in &src {
+            let bucket = &mut counts[(item.0 >> shift) as usize & 0xff];
+            dst[*bucket] = item;
+            *bucket += 1;
+        }
+        core::mem::swap(&mut src, &mut dst);
+    }
+    for (x, &(_, y)) in v.iter_mut().zip(&src) {
+        *x = y;
+    }
+}
+
+/// Reorder a slice so that the element at index `k` is the one that would be
+/// there after `sort`, and return it.
+///
+/// Everything before index `k` is then less than or equal to it, and
+/// everything after is greater than or equal to it, in the total ordering.
+///
+/// # Allocation behavior
+///
+/// This routine uses a quickselect implementation that does not heap
+/// allocate.
+///
+/// # Panics
+///
+/// Panics if `k >= v.len()`.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [-5.0, 4.0, f64::NAN, -3.0, 2.0];
+///
+/// assert_eq!(*float_ord::select_nth(&mut v, 1), -3.0);
+/// assert!(float_ord::select_nth(&mut v, 4).is_nan());
+/// ```
+pub fn select_nth<T>(v: &mut [T], k: usize) -> &T
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice_mut(v);
+    &v_.select_nth_unstable(k).1 .0
+}
+
+/// Rearrange a slice of floats into the elements less than `pivot`, then
+/// those equal to it, then those greater, according to the total ordering.
+///
+/// Returns `(lt, gt)`, so that `v[..lt]` is less than the pivot, `v[lt..gt]`
+/// is equal to it and `v[gt..]` is greater. Equal means the same bit
+/// pattern, so `-0` and `+0` end up in different regions. The order within
+/// each region is unspecified.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [3.0f64, 0.0, -0.0, 1.0, 0.0, -2.0];
+///
+/// let (lt, gt) = float_ord::partition3(&mut v, &0.0);
+/// assert_eq!((lt, gt), (2, 4));
+/// assert!(v[lt..gt].iter().all(|x| x.is_sign_positive() && *x == 0.0));
+/// ```
+pub fn partition3<T>(v: &mut [T], pivot: &T) -> (usize, usize)
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice_mut(v);
+    let pivot_ = FloatOrd::from_ref(pivot);
+    let (mut lt, mut i, mut gt) = (0, 0, v_.len());
+    while i < gt {
+        match v_[i].cmp(pivot_) {
+            Ordering::Less => {
+                v_.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Equal => i += 1,
+            Ordering::Greater => {
+                gt -= 1;
+                v_.swap(i, gt);
+            }
+        }
+    }
+    (lt, gt)
+}
+
+/// Return the median of a slice of floats, or `None` if it is empty.
+///
+/// For an even number of elements, this is the arithmetic mean of the two
+/// middle elements, computed without overflowing even near `MAX`. NaN take
+/// part like any other value: positive NaN sort at
+/// the top and negative NaN at the bottom, so if enough of them are present
+/// the result is NaN.
+///
+/// The slice is reordered, as by `select_nth`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(float_ord::median(&mut [3.0, -1.0, 2.0]), Some(2.0));
+/// assert_eq!(float_ord::median(&mut [3.0, -1.0, 2.0, 0.0]), Some(1.0));
+/// assert_eq!(float_ord::median::<f64>(&mut []), None);
+/// ```
+pub fn median<T>(v: &mut [T]) -> Option<T>
+    where T: Copy + Add<Output = T> + Sub<Output = T> + Div<Output = T> + From<u8>,
+          FloatOrd<T>: Ord
+{
+    let n = v.len();
+    if n == 0 {
+        return None;
+    }
+    let upper = *select_nth(v, n / 2);
+    if n % 2 == 1 {
+        return Some(upper);
+    }
+    let lower = v[..n / 2].iter().cloned().float_max().unwrap();
+    if FloatOrd(lower) == FloatOrd(upper) {
+        return Some(upper);
+    }
+    // Adding two values of the same sign can overflow, but their difference
+    // can't. Values of opposite signs are the other way round.
+    let zero = FloatOrd(T::from(0));
+    if (FloatOrd(lower) < zero) == (FloatOrd(upper) < zero) {
+        Some(lower + (upper - lower) / T::from(2))
+    } else {
+        Some((lower + upper) / T::from(2))
+    }
+}
+
+/// Return the value at quantile `q` of a slice of floats, or `None` if it is
+/// empty.
+///
+/// This uses the nearest-rank method: the result is the element at rank
+/// `ceil(q * len)` in the total ordering, counting from one, or the smallest
+/// element when `q` is zero. No interpolation is done, so the result is
+/// always an element of the slice. Positive NaN sort at the top, so a high
+/// enough quantile of data containing NaN is NaN.
+///
+/// The slice is reordered, as by `select_nth`.
+///
+/// # Panics
+///
+/// Panics if `q` is not between `0.0` and `1.0`, inclusive.
+///
+/// # Example
+///
+/// ```
+/// let mut v = [5.0, 1.0, 4.0, 2.0, 3.0];
+///
+/// assert_eq!(float_ord::quantile(&mut v, 0.0), Some(1.0));
+/// assert_eq!(float_ord::quantile(&mut v, 0.5), Some(3.0));
+/// assert_eq!(float_ord::quantile(&mut v, 0.9), Some(5.0));
+/// ```
+pub fn quantile<T>(v: &mut [T], q: f64) -> Option<T>
+    where T: Copy,
+          FloatOrd<T>: Ord
+{
+    assert!((0.0..=1.0).contains(&q), "quantile must be between 0 and 1");
+    if v.is_empty() {
+        return None;
+    }
+    let x = q * v.len() as f64;
+    let rank = x as usize;
+    let rank = if (rank as f64) < x { rank + 1 } else { rank };
+    Some(*select_nth(v, cmp::max(rank, 1) - 1))
+}
+
+#[cfg(feature="alloc")]
+/// Return the most frequent float in a slice, or `None` if it is empty.
+///
+/// Values are counted by the total equality, so `-0` and `+0` are counted
+/// separately, and NaN are counted by their exact bits (unless the
+/// `nan-at-top` feature is enabled, which makes every NaN equal). If several
+/// values are equally frequent, the smallest is returned.
+///
+/// The slice is sorted, as by `sort`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(float_ord::mode(&mut [2.0, 1.0, 2.0, 3.0]), Some(2.0));
+/// assert_eq!(float_ord::mode(&mut [3.0, 1.0]), Some(1.0));
+/// assert_eq!(float_ord::mode::<f64>(&mut []), None);
+/// ```
+pub fn mode<T>(v: &mut [T]) -> Option<T>
+    where T: Copy,
+          FloatOrd<T>: Ord
+{
+    sort(v);
+    let v_ = as_ord_slice(v);
+    let mut best = (0, 0);
+    let mut start = 0;
+    for i in 1..v_.len() + 1 {
+        if i == v_.len() || v_[i] != v_[start] {
+            if i - start > best.1 {
+                best = (start, i - start);
+            }
+            start = i;
+        }
+    }
+    v.get(best.0).cloned()
+}
+
+/// Return `true` if the slice is sorted by the total ordering, smallest
+/// first.
+///
+/// # Example
+///
+/// ```
+/// assert!(float_ord::is_sorted(&[-0.0, 0.0, 1.0, f64::NAN]));
+/// assert!(!float_ord::is_sorted(&[0.0, -0.0]));
+/// ```
+pub fn is_sorted<T>(v: &[T]) -> bool
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice(v);
+    v_.windows(2).all(|w| w[0] <= w[1])
+}
+
+/// Return `true` if the slice is sorted by the total ordering, largest
+/// first.
+///
+/// # Example
+///
+/// ```
+/// assert!(float_ord::is_sorted_descending(&[f64::NAN, 1.0, 0.0, -0.0]));
+/// assert!(!float_ord::is_sorted_descending(&[-0.0, 0.0]));
+/// ```
+pub fn is_sorted_descending<T>(v: &[T]) -> bool
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice(v);
+    v_.windows(2).all(|w| w[0] >= w[1])
+}
+
+/// Return `true` if the slice is strictly increasing by the total ordering,
+/// with no two adjacent elements equal.
+///
+/// # Example
+///
+/// ```
+/// assert!(float_ord::is_strictly_sorted(&[-0.0, 0.0, 1.0, f64::NAN]));
+/// assert!(!float_ord::is_strictly_sorted(&[0.0, 0.0]));
+/// ```
+pub fn is_strictly_sorted<T>(v: &[T]) -> bool
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice(v);
+    v_.windows(2).all(|w| w[0] < w[1])
+}
+
+/// Panic unless the slice is sorted by the total ordering, smallest first.
+///
+/// This is meant for test suites. The panic message gives the first index
+/// where the order breaks, along with the two values, printed with
+/// `FloatOrd`'s `Debug` so the sign of zeros and NaN is visible.
+///
+/// # Panics
+///
+/// Panics if some element sorts after the one following it.
+///
+/// # Example
+///
+/// ```
+/// float_ord::assert_total_order(&[-f64::INFINITY, -0.0, 0.0, 1.0, f64::NAN]);
+/// ```
+#[track_caller]
+pub fn assert_total_order<T>(values: &[T])
+    where FloatOrd<T>: Ord + fmt::Debug
+{
+    let v_ = as_ord_slice(values);
+    if let Some(i) = v_.windows(2).position(|w| w[0] > w[1]) {
+        panic!("values are not in total order: index {} ({:?}) sorts after index {} ({:?})",
+               i, v_[i], i + 1, v_[i + 1]);
+    }
+}
+
+/// Binary search a slice of floats sorted by the total ordering.
+///
+/// Returns `Ok` with the index of a matching element, or `Err` with the index
+/// where `target` could be inserted while keeping the slice sorted. Unlike
+/// `slice::binary_search_by` with `partial_cmp`, this finds NaN entries.
+///
+/// # Example
+///
+/// ```
+/// let v = [-5.0, -3.0, 1.0, 2.0, 4.0, f64::NAN];
+///
+/// assert_eq!(float_ord::binary_search(&v, &2.0), Ok(3));
+/// assert_eq!(float_ord::binary_search(&v, &f64::NAN), Ok(5));
+/// assert_eq!(float_ord::binary_search(&v, &0.0), Err(2));
+/// ```
+pub fn binary_search<T>(v: &[T], target: &T) -> Result<usize, usize>
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice(v);
+    let target_ = FloatOrd::from_ref(target);
+    v_.binary_search(target_)
+}
+
+/// Binary search a slice sorted by a float key, according to the total
+/// ordering.
+///
+/// The slice must be sorted by the key returned by `f`, for example by
+/// `sort_by_key`. Returns `Ok` with the index of an element whose key
+/// matches, or `Err` with the index where such an element could be inserted.
+///
+/// # Example
+///
+/// ```
+/// let v = [(-1.0, 'a'), (0.5, 'b'), (0.5, 'c'), (2.0, 'd'), (f64::NAN, 'e')];
+///
+/// assert_eq!(float_ord::search_by_key(&v, &2.0, |x| x.0), Ok(3));
+/// assert_eq!(float_ord::search_by_key(&v, &f64::NAN, |x| x.0), Ok(4));
+/// assert_eq!(float_ord::search_by_key(&v, &0.0, |x| x.0), Err(1));
+/// ```
+pub fn search_by_key<T, K, F>(v: &[T], key: &K, mut f: F) -> Result<usize, usize>
+    where F: FnMut(&T) -> K,
+          FloatOrd<K>: Ord
+{
+    let key_ = FloatOrd::from_ref(key);
+    v.binary_search_by(|x| FloatOrd(f(x)).cmp(key_))
+}
+
+/// Return the index of the first element for which `pred` is false, in a
+/// slice sorted by the total ordering.
+///
+/// The predicate must be true for a prefix of the slice and false for the
+/// rest, as with `slice::partition_point`. Predicates built from `FloatOrd`
+/// comparisons have this property on slices sorted by `sort`, even when the
+/// slice contains NaN.
+///
+/// # Example
+///
+/// ```
+/// use float_ord::FloatOrd;
+///
+/// let v = [-1.0, 0.5, 2.0, 3.0, f64::NAN];
+/// let i = float_ord::partition_point(&v, |&x| FloatOrd(x) < FloatOrd(2.0));
+/// assert_eq!(i, 2);
+/// ```
+pub fn partition_point<T, P>(v: &[T], pred: P) -> usize
+    where P: FnMut(&T) -> bool
+{
+    v.partition_point(pred)
+}
+
+/// Return the histogram bin that `value` falls in, given sorted bin edges.
+///
+/// The result is the number of edges less than or equal to `value`, according
+/// to the total ordering: `0` for values below the first edge, and
+/// `edges.len()` for values at or above the last one. Positive NaN always
+/// lands in the top bin.
+///
+/// # Example
+///
+/// ```
+/// let edges = [0.0, 1.0, 2.0];
+///
+/// assert_eq!(float_ord::bin_index(&edges, &-5.0), 0);
+/// assert_eq!(float_ord::bin_index(&edges, &1.0), 2);
+/// assert_eq!(float_ord::bin_index(&edges, &1.5), 2);
+/// assert_eq!(float_ord::bin_index(&edges, &f64::NAN), 3);
+/// ```
+pub fn bin_index<T>(edges: &[T], value: &T) -> usize
+    where FloatOrd<T>: Ord
+{
+    let value_ = FloatOrd::from_ref(value);
+    edges.partition_point(|x| FloatOrd::from_ref(x) <= value_)
+}
+
+/// Return the fraction of elements in a sorted slice that are less than or
+/// equal to `value`, according to the total ordering.
+///
+/// The slice must already be sorted, for example by `sort`. An empty slice
+/// gives NaN.
+///
+/// # Example
+///
+/// ```
+/// let v = [1.0, 2.0, 2.0, 3.0];
+///
+/// assert_eq!(float_ord::percentile_rank(&v, &2.0), 0.75);
+/// assert_eq!(float_ord::percentile_rank(&v, &0.0), 0.0);
+/// assert_eq!(float_ord::percentile_rank(&v, &f64::NAN), 1.0);
+/// ```
+pub fn percentile_rank<T>(sorted: &[T], value: &T) -> f64
+    where FloatOrd<T>: Ord
+{
+    let v_ = as_ord_slice(sorted);
+    let value_ = FloatOrd::from_ref(value);
+    let count = v_.partition_point(|x| x <= value_);
+    count as f64 / sorted.len() as f64
+}
+
+/// Return the number of elements in a sorted slice that are strictly less
+/// than `value`, according to the total ordering.
+///
+/// The slice must already be sorted, for example by `sort`. Unlike
+/// `bin_index`, elements equal to `value` are not counted. A NaN query
+/// counts every element that sorts below it.
+///
+/// # Example
+///
+/// ```
+/// let v = [1.0, 2.0, 2.0, 3.0];
+///
+/// assert_eq!(float_ord::count_less_than(&v, &2.0), 1);
+/// assert_eq!(float_ord::bin_index(&v, &2.0), 3);
+/// assert_eq!(float_ord::count_less_than(&v, &f64::NAN), 4);
+/// ```
+pub fn count_less_than<T>(sorted: &[T], value: &T) -> usize
+    where FloatOrd<T>: Ord
+{
+    let value_ = FloatOrd::from_ref(value);
+    as_ord_slice(sorted).partition_point(|x| x < value_)
+}
+
+#[cfg(feature="alloc")]
+/// Remove consecutive repeated floats from a vector, according to the total
+/// equality.
+///
+/// Unlike `Vec::dedup`, repeated NaN with the same bit pattern are removed,
+/// while `-0` and `+0` are kept apart.
+///
+/// # Example
+///
+/// ```
+/// let mut v = vec![-0.0, 0.0, 1.0, 1.0, f64::NAN, f64::NAN];
+///
+/// float_ord::dedup(&mut v);
+/// assert_eq!(v.len(), 4);
+/// assert!(v[0].is_sign_negative() && v[1].is_sign_positive());
+/// assert!(v[3].is_nan());
+/// ```
+pub fn dedup<T>(v: &mut Vec<T>)
+    where FloatOrd<T>: Ord
+{
+    v.dedup_by(|a, b| FloatOrd::from_ref(a) == FloatOrd::from_ref(b));
+}
+
+#[cfg(feature="alloc")]
+/// Remove consecutive floats that are within `ulps` steps of each other in
+/// the total ordering.
+///
+/// An element is removed if it is the same `FloatOrd` as the previous kept
+/// element, or at most `ulps` steps away from it as counted by
+/// `FloatOrd::within_ulps`. Tolerance-based dedup depends on iteration
+/// order: each element is compared with the last one kept, not with its
+/// neighbour, so a run of closely spaced values is split wherever it moves
+/// more than `ulps` steps past the last kept value. With `ulps` of 2, the
+/// run `1`, `1 + ε`, `1 + 2ε`, `1 + 3ε` becomes `1`, `1 + 3ε`. Sort the
+/// vector first so that near-equal values are adjacent.
+///
+/// # Example
+///
+/// ```
+/// use float_ord::FloatOrd;
+///
+/// let a = FloatOrd(1.0f64);
+/// let mut v = vec![a.0, a.next_up().0, a.next_up().next_up().0, 2.0];
+///
+/// float_ord::dedup_by_ulps(&mut v, 2);
+/// assert_eq!(v, [1.0, 2.0]);
+/// ```
+pub fn dedup_by_ulps<T>(v: &mut Vec<T>, ulps: u64)
+    where T: Float,
+          FloatOrd<T>: Ord
+{
+    v.dedup_by(|a, b| FloatOrd::from_ref(a) == FloatOrd::from_ref(b) || b.within_ulps(*a, ulps));
+}
+
+/// Method syntax for sorting and searching slices of floats.
+///
+/// # Example
+///
+/// ```
+/// use float_ord::FloatSliceExt;
+///
+/// let v = [-3.0, -0.0, 0.0, 2.0, f64::NAN];
+/// assert_eq!(v.float_binary_search(&0.0), Ok(2));
+/// assert_eq!(v.float_binary_search(&f64::NAN), Ok(4));
+/// ```
+pub trait FloatSliceExt<T> {
+    /// Sort the slice; see `sort`.
+    #[cfg(any(feature="pdqsort", feature="alloc"))]
+    fn float_sort(&mut self);
+    /// Binary search the sorted slice; see `binary_search`.
+    fn float_binary_search(&self, target: &T) -> Result<usize, usize>;
+}
+
+impl<T> FloatSliceExt<T> for [T]
+    where FloatOrd<T>: Ord
+{
+    #[cfg(any(feature="pdqsort", feature="alloc"))]
+    fn float_sort(&mut self) {
+        sort(self)
+    }
+    fn float_binary_search(&self, target: &T) -> Result<usize, usize> {
+        binary_search(self, target)
+    }
+}
+
+/// Method syntax for finding the smallest and largest float in an iterator.
+///
+/// # Example
+///
+/// ```
+/// use float_ord::FloatIterExt;
+///
+/// let v = [1.0, f64::NAN, -2.0];
+/// assert_eq!(v.iter().cloned().float_min(), Some(-2.0));
+/// assert!(v.iter().cloned().float_max().unwrap().is_nan());
+/// ```
+pub trait FloatIterExt<T> {
+    /// Return the smallest element, according to the total ordering, or
+    /// `None` if the iterator is empty.
+    fn float_min(self) -> Option<T>;
+    /// Return the largest element, according to the total ordering, or
+    /// `None` if the iterator is empty.
+    fn float_max(self) -> Option<T>;
+}
+
+impl<T, I> FloatIterExt<T> for I
+    where I: Iterator<Item = T>,
+          FloatOrd<T>: Ord
+{
+    fn float_min(self) -> Option<T> {
+        self.map(FloatOrd).min().map(|f| f.0)
+    }
+    fn float_max(self) -> Option<T> {
+        self.map(FloatOrd).max().map(|f| f.0)
+    }
+}
+
+/// Chain float comparisons onto an `Ordering`.
+///
+/// # Example
+///
+/// ```
+/// use float_ord::FloatOrderingExt;
+///
+/// let mut v = [(1.0, 2.0), (0.0, 5.0), (1.0, -1.0)];
+/// v.sort_by(|a, b| float_ord::compare(&a.0, &b.0).then_float(&a.1, &b.1));
+/// assert_eq!(v, [(0.0, 5.0), (1.0, -1.0), (1.0, 2.0)]);
+/// ```
+pub trait FloatOrderingExt {
+    /// Return `self` unless it is `Equal`, in which case compare `a` and `b`
+    /// according to the total ordering.
+    fn then_float<T>(self, a: &T, b: &T) -> Ordering
+        where FloatOrd<T>: Ord;
+}
+
+impl FloatOrderingExt for Ordering {
+    fn then_float<T>(self, a: &T, b: &T) -> Ordering
+        where FloatOrd<T>: Ord
+    {
+        self.then_with(|| compare(a, b))
+    }
+}
+
+#[cfg(test)]
+// The original tests predate these lints.
+#[allow(unused_parens, clippy::double_parens, clippy::legacy_numeric_constants,
+        clippy::approx_constant)]
+mod tests {
+    extern crate std;
+    extern crate rand;
+
+    use self::rand::{Rng, thread_rng};
+    use self::std::prelude::v1::*;
+    use self::std::collections::hash_map::DefaultHasher;
+    use self::std::hash::{Hash, Hasher};
+    use self::std::f64;
+    use super::FloatOrd;
+    use self::std::cmp::Ordering;
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_ord() {
+        assert!(FloatOrd(1.0f64) < FloatOrd(2.0f64));
+        assert!(FloatOrd(2.0f32) > FloatOrd(1.0f32));
+        assert!(FloatOrd(1.0f64) == FloatOrd(1.0f64));
+        assert!(FloatOrd(1.0f32) == FloatOrd(1.0f32));
+        assert!(FloatOrd(0.0f64) > FloatOrd(-0.0f64));
+        assert!(FloatOrd(0.0f32) > FloatOrd(-0.0f32));
+        assert!(FloatOrd(::core::f64::NAN) == FloatOrd(::core::f64::NAN));
+        assert!(FloatOrd(::core::f32::NAN) == FloatOrd(::core::f32::NAN));
+        assert!(FloatOrd(-::core::f64::NAN) < FloatOrd(::core::f64::NAN));
+        assert!(FloatOrd(-::core::f32::NAN) < FloatOrd(::core::f32::NAN));
+        assert!(FloatOrd(-::core::f64::INFINITY) < FloatOrd(::core::f64::INFINITY));
+        assert!(FloatOrd(-::core::f32::INFINITY) < FloatOrd(::core::f32::INFINITY));
+        assert!(FloatOrd(::core::f64::INFINITY) < FloatOrd(::core::f64::NAN));
+        assert!(FloatOrd(::core::f32::INFINITY) < FloatOrd(::core::f32::NAN));
+        assert!(FloatOrd(-::core::f64::NAN) < FloatOrd(::core::f64::INFINITY));
+        assert!(FloatOrd(-::core::f32::NAN) < FloatOrd(::core::f32::INFINITY));
+    }
+
+    #[test]
+    fn test_ord_numbers() {
+        let mut rng = thread_rng();
+        for n in 0..16 {
+            for l in 0..16 {
+                let v = rng.gen_iter::<f64>()
+                    .map(|x| x % (1 << l) as i64 as f64)
+                    .take((1 << n))
+                    .collect::<Vec<_>>();
+                assert!(v.windows(2)
+                            .all(|w| (w[0] <= w[1]) == (FloatOrd(w[0]) <= FloatOrd(w[1]))));
+            }
+        }
+    }
+
+    fn hash<F: Hash>(f: F) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        f.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_hash() {
+        assert_ne!(hash(FloatOrd(0.0f64)), hash(FloatOrd(-0.0f64)));
+        assert_ne!(hash(FloatOrd(0.0f32)), hash(FloatOrd(-0.0f32)));
+        assert_eq!(hash(FloatOrd(-0.0f64)), hash(FloatOrd(-0.0f64)));
+        assert_eq!(hash(FloatOrd(0.0f32)), hash(FloatOrd(0.0f32)));
+        assert_ne!(hash(FloatOrd(::core::f64::NAN)),
+                   hash(FloatOrd(-::core::f64::NAN)));
+        assert_ne!(hash(FloatOrd(::core::f32::NAN)),
+                   hash(FloatOrd(-::core::f32::NAN)));
+        assert_eq!(hash(FloatOrd(::core::f64::NAN)),
+                   hash(FloatOrd(::core::f64::NAN)));
+        assert_eq!(hash(FloatOrd(-::core::f32::NAN)),
+                   hash(FloatOrd(-::core::f32::NAN)));
+    }
+
+    #[cfg(any(feature="pdqsort", feature="alloc"))]
+    #[test]
+    fn test_sort_numbers() {
+        let mut rng = thread_rng();
+        for n in 0..16 {
+            for l in 0..16 {
+                let mut v = rng.gen_iter::<f64>()
+                    .map(|x| x % (1 << l) as i64 as f64)
+                    .take((1 << n))
+                    .collect::<Vec<_>>();
+                let mut v1 = v.clone();
+
+                super::sort(&mut v);
+                assert!(v.windows(2).all(|w| w[0] <= w[1]));
+
+                v1.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                assert!(v1.windows(2).all(|w| w[0] <= w[1]));
+
+                v1.sort_by(|a, b| b.partial_cmp(a).unwrap());
+                assert!(v1.windows(2).all(|w| w[0] >= w[1]));
+            }
+        }
+
+        let mut v = [5.0];
+        super::sort(&mut v);
+        assert!(v == [5.0]);
+    }
+
+    #[cfg(any(feature="pdqsort", feature="alloc"))]
+    #[test]
+    fn test_sort_nan() {
+        let nan = ::core::f64::NAN;
+        let mut v = [-1.0, 5.0, 0.0, -0.0, nan, 1.5, nan, 3.7];
+        super::sort(&mut v);
+        assert!(v[0] == -1.0);
+        assert!(v[1] == 0.0 && v[1].is_sign_negative());
+        assert!(v[2] == 0.0 && !v[2].is_sign_negative());
+        assert!(v[3] == 1.5);
+        assert!(v[4] == 3.7);
+        assert!(v[5] == 5.0);
+        assert!(v[6].is_nan());
+        assert!(v[7].is_nan());
+    }
+
+    // Only the test harness links `std` here; the crate itself sees `alloc`.
+    #[cfg(all(feature="alloc", not(feature="std")))]
+    #[test]
+    fn test_alloc_without_std() {
+        let v = [2.0f64, f64::NAN, -1.0];
+        assert_eq!(super::argsort(&v), [2, 0, 1]);
+        assert_eq!(super::top_k(&v, 1).len(), 1);
+        let mut w = vec![1.0f32, 1.0, -0.0, 0.0];
+        super::dedup(&mut w);
+        assert_eq!(w.len(), 3);
+    }
+
+    #[cfg(any(feature="pdqsort", feature="alloc"))]
+    #[test]
+    fn test_sort_floats() {
+        let nan = f64::NAN;
+        let mut v = [3.0, -nan, -0.0, nan, -f64::INFINITY, 0.0, -nan, 1.0];
+        super::sort_floats(&mut v);
+        assert_eq!(v[0], -f64::INFINITY);
+        assert!(v[1] == 0.0 && v[1].is_sign_negative());
+        assert!(v[2] == 0.0 && v[2].is_sign_positive());
+        assert_eq!(&v[3..5], &[1.0, 3.0]);
+        assert!(v[5..].iter().all(|x| x.is_nan()));
+
+        let mut v = [-f32::NAN, -f32::NAN];
+        super::sort_floats(&mut v);
+        assert!(v.iter().all(|x| x.is_nan()));
+        let mut v = [2.0f32, 1.0];
+        super::sort_floats(&mut v);
+        assert_eq!(v, [1.0, 2.0]);
+    }
+
+    #[cfg(feature="pdqsort")]
+    #[test]
+    fn test_sort_with_threshold() {
+        let mut rng = thread_rng();
+        let threshold = 8;
+        for &n in &[0, 1, threshold - 1, threshold, threshold + 1] {
+            let mut v = rng.gen_iter::<f64>().map(|x| x - 0.5).take(n).collect::<Vec<_>>();
+            if n > 2 {
+                v[n / 2] = f64::NAN;
+                v[0] = -0.0;
+                v[1] = 0.0;
+            }
+            let mut v1 = v.clone();
+            super::sort_with_threshold(&mut v, threshold);
+            v1.sort_by_key(|&x| FloatOrd(x));
+            assert!(v.iter().zip(&v1).all(|(a, b)| a.to_bits() == b.to_bits()));
+        }
+    }
+
+    #[cfg(feature="pdqsort")]
+    #[test]
+    fn test_sort_descending_nan() {
+        let nan = f64::NAN;
+        let inf = f64::INFINITY;
+        let mut v = [-1.0, 5.0, 0.0, -0.0, nan, 1.5, inf, nan, 3.7];
+        super::sort_descending(&mut v);
+        assert!(v[0].is_nan());
+        assert!(v[1].is_nan());
+        assert!(v[2] == inf);
+        assert!(v[3] == 5.0);
+        assert!(v[4] == 3.7);
+        assert!(v[5] == 1.5);
+        assert!(v[6] == 0.0 && !v[6].is_sign_negative());
+        assert!(v[7] == 0.0 && v[7].is_sign_negative());
+        assert!(v[8] == -1.0);
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_sort_stable() {
+        let nan = f64::NAN;
+        let mut v = [-1.0, 5.0, 0.0, -0.0, nan, 1.5, nan, 3.7];
+        super::sort_stable(&mut v);
+        assert!(v[0] == -1.0);
+        assert!(v[1] == 0.0 && v[1].is_sign_negative());
+        assert!(v[2] == 0.0 && !v[2].is_sign_negative());
+        assert!(v[3] == 1.5);
+        assert!(v[4] == 3.7);
+        assert!(v[5] == 5.0);
+        assert!(v[6].is_nan());
+        assert!(v[7].is_nan());
+
+        let mut v = (0..1000)
+            .map(|i| ((i % 7) as f64 - 3.0, i))
+            .collect::<Vec<_>>();
+        super::sort_stable_by_key(&mut v, |x| x.0);
+        assert!(v.windows(2)
+                    .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+    }
+
+    #[cfg(feature="pdqsort")]
+    #[test]
+    fn test_sort_by_then() {
+        use super::sort_by_then;
+        let mut rng = thread_rng();
+        let mut v = rng.gen_iter::<u8>()
+            .map(|x| f64::from(x % 5))
+            .take(100)
+            .collect::<Vec<_>>();
+        v.extend_from_slice(&[f64::NAN, -0.0, f64::NAN, 0.0]);
+        let mut ties = 0;
+        sort_by_then(&mut v, |a, b| {
+            assert_eq!(FloatOrd(*a), FloatOrd(*b));
+            ties += 1;
+            Ordering::Equal
+        });
+        assert!(ties > 0);
+        assert!(super::is_sorted(&v));
+        assert!(v[0] == 0.0 && v[0].is_sign_negative());
+        assert!(v[v.len() - 2..].iter().all(|x| x.is_nan()));
+    }
+
+    #[cfg(all(feature="pdqsort", feature="nan-at-top"))]
+    #[test]
+    fn test_sort_by_then_nan_payload() {
+        let payload = |p: u64| f64::from_bits(f64::NAN.to_bits() | p);
+        let mut v = [payload(3), 1.0, payload(1), -f64::NAN, payload(2)];
+        super::sort_by_then(&mut v, |a, b| b.to_bits().cmp(&a.to_bits()));
+        assert_eq!(v[0], 1.0);
+        assert_eq!(v[1].to_bits(), (-f64::NAN).to_bits());
+        let bits = v[2..].iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits, [payload(3).to_bits(), payload(2).to_bits(), payload(1).to_bits()]);
+    }
+
+    #[cfg(feature="pdqsort")]
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_sort_by_key() {
+        #[derive(Debug)]
+        struct Particle {
+            mass: f64,
+            id: u32,
+        }
+        let mut v = vec![
+            Particle { mass: 3.0, id: 0 },
+            Particle { mass: f64::NAN, id: 1 },
+            Particle { mass: -0.0, id: 2 },
+            Particle { mass: 0.0, id: 3 },
+            Particle { mass: -f64::NAN, id: 4 },
+            Particle { mass: -2.5, id: 5 },
+        ];
+        super::sort_by_key(&mut v, |p| p.mass);
+        let ids = v.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(ids, [4, 5, 2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn test_const_cmp() {
+        use super::{const_cmp_f32, const_cmp_f64};
+        const ZEROS: Ordering = const_cmp_f64(-0.0, 0.0);
+        const NAN_INF: Ordering = const_cmp_f64(f64::NAN, f64::INFINITY);
+        const NAN_NAN: Ordering = const_cmp_f32(f32::NAN, f32::NAN);
+        const ONE_TWO: Ordering = const_cmp_f32(2.0, 1.0);
+        assert_eq!(ZEROS, Ordering::Less);
+        assert_eq!(NAN_INF, Ordering::Greater);
+        assert_eq!(NAN_NAN, Ordering::Equal);
+        assert_eq!(ONE_TWO, Ordering::Greater);
+        let grid = [-f64::NAN, -f64::INFINITY, -1.0, -0.0, 0.0, 1e-310, f64::INFINITY, f64::NAN];
+        for &a in &grid {
+            for &b in &grid {
+                assert_eq!(const_cmp_f64(a, b), FloatOrd(a).cmp(&FloatOrd(b)));
+                assert_eq!(const_cmp_f32(a as f32, b as f32),
+                           FloatOrd(a as f32).cmp(&FloatOrd(b as f32)));
+            }
+        }
+    }
+
+    #[cfg(feature="step")]
+    #[test]
+    fn test_step() {
+        let zero = FloatOrd(0.0f64);
+        let v = (FloatOrd(-0.0f64).next_down()..zero.next_up().next_up()).collect::<Vec<_>>();
+        assert_eq!(v.len(), 4);
+        assert!(v.windows(2).all(|w| w[0].next_up() == w[1]));
+        assert_eq!((zero..zero).count(), 0);
+        assert_eq!((FloatOrd(1.0f32)..FloatOrd(2.0)).count(), 1 << 23);
+        assert_eq!((FloatOrd(1.0f32)..=FloatOrd(2.0)).step_by(1 << 22).count(), 3);
+        assert_eq!((zero..=zero).rev().collect::<Vec<_>>(), [zero]);
+        let big = FloatOrd(f64::MAX);
+        assert_eq!((big..=FloatOrd(f64::INFINITY)).count(), 2);
+    }
+
+    #[cfg(all(feature="step", feature="nan-at-top"))]
+    #[test]
+    fn test_step_nan_at_top() {
+        use core::iter::Step;
+        let inf = FloatOrd(f64::INFINITY);
+        let nan = FloatOrd(f64::NAN);
+        let v = (inf..=FloatOrd(-f64::NAN)).collect::<Vec<_>>();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0], inf);
+        assert!(v[1].0.is_nan());
+        assert_eq!((inf..nan).count(), 1);
+        assert_eq!((nan..=nan).rev().count(), 1);
+        assert_eq!((FloatOrd(f32::MAX)..=FloatOrd(f32::NAN)).count(), 3);
+        assert_eq!(Step::forward_checked(nan, 1), None);
+        assert_eq!(Step::backward_checked(nan, 1), Some(inf));
+        assert_eq!(Step::backward_checked(FloatOrd(f64::NEG_INFINITY), 1), None);
+        assert_eq!(Step::steps_between(&FloatOrd(f64::NEG_INFINITY), &nan).0,
+                   (FloatOrd(f64::INFINITY).to_ord_bits() - FloatOrd(f64::NEG_INFINITY).to_ord_bits()
+                    + 1) as usize);
+    }
+
+    #[test]
+    fn test_ulp_range() {
+        use super::ulp_range;
+        let zero = FloatOrd(0.0f64);
+        assert_eq!(ulp_range(zero, zero.next_up(), 1).collect::<Vec<_>>(), [zero]);
+        assert_eq!(ulp_range(zero, zero, 1).count(), 0);
+        assert_eq!(ulp_range(zero.next_up(), zero, 1).count(), 0);
+        let v = ulp_range(FloatOrd(-0.0f64).next_down(), zero.next_up().next_up(), 1)
+            .collect::<Vec<_>>();
+        assert_eq!(v.len(), 4);
+        assert!(v.windows(2).all(|w| w[0].next_up() == w[1]));
+        assert!(v[1].0.is_sign_negative() && v[2].0.is_sign_positive());
+        let one = FloatOrd(1.0f32);
+        assert_eq!(ulp_range(one, FloatOrd(2.0), 1).count(), 1 << 23);
+        assert_eq!(ulp_range(one, FloatOrd(2.0), 1 << 20).count(), 8);
+        assert_eq!(ulp_range(one, FloatOrd(2.0), u64::MAX).collect::<Vec<_>>(), [one]);
+        let big = FloatOrd(f64::MAX);
+        assert_eq!(ulp_range(big, FloatOrd(f64::INFINITY), 1).collect::<Vec<_>>(), [big]);
+    }
+
+    #[test]
+    fn test_midpoint_bits() {
+        use super::midpoint_bits;
+        let pairs = [(-f64::INFINITY, f64::INFINITY), (1.0, 1.0 + f64::EPSILON),
+                     (-f64::MAX, 1e-300), (0.0, f64::NAN), (-f64::NAN, f64::NAN),
+                     (3.0, 3.0)];
+        for &(a, b) in &pairs {
+            let (mut lo, mut hi) = (FloatOrd(a), FloatOrd(b));
+            let mut steps = 0;
+            loop {
+                let mid = midpoint_bits(lo, hi);
+                assert!(lo <= mid && mid <= hi);
+                if mid == lo {
+                    break;
+                }
+                // Alternate between keeping the lower and upper halves.
+                if steps % 2 == 0 {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+                steps += 1;
+            }
+            assert!(steps <= 64);
+            assert!(lo == hi || lo.next_up() == hi);
+        }
+        assert_eq!(midpoint_bits(FloatOrd(2.0f32), FloatOrd(8.0)), FloatOrd(4.0));
+        assert_eq!(midpoint_bits(FloatOrd(8.0f64), FloatOrd(2.0)), FloatOrd(4.0));
+    }
 
-#[cfg(test)]
-mod tests {
-    extern crate std;
-    extern crate rand;
+    #[test]
+    fn test_min_max_by_key() {
+        use super::{min_by_key, max_by_key};
+        struct Sample {
+            name: &'static str,
+            score: f64,
+        }
+        let v = [Sample { name: "a", score: 1.0 },
+                 Sample { name: "b", score: f64::NAN },
+                 Sample { name: "c", score: -3.0 },
+                 Sample { name: "d", score: f64::INFINITY },
+                 Sample { name: "e", score: -3.0 }];
+        assert_eq!(max_by_key(&v, |s| s.score).unwrap().name, "b");
+        assert_eq!(min_by_key(&v, |s| s.score).unwrap().name, "c");
+        assert_eq!(max_by_key(&v[2..], |s| s.score).unwrap().name, "d");
+        assert_eq!(max_by_key(&v[2..], |s| -s.score).unwrap().name, "e");
+        assert!(min_by_key(&[] as &[Sample], |s| s.score).is_none());
+        assert!(max_by_key(&[0.0f32, -0.0], |&x| x).unwrap().is_sign_positive());
+    }
+
+    #[test]
+    fn test_compare_then_float() {
+        use super::{compare, FloatOrderingExt};
+        let mut v = [(0.0, 1.0), (-0.0, 3.0), (0.0, -2.0), (f64::NAN, 0.0), (-0.0, f64::NAN),
+                     (-1.0, 9.0)];
+        v.sort_by(|a, b| compare(&a.0, &b.0).then_float(&a.1, &b.1));
+        assert_eq!(v[0], (-1.0, 9.0));
+        assert!(v[1].0.is_sign_negative() && v[1].1 == 3.0);
+        assert!(v[2].0.is_sign_negative() && v[2].1.is_nan());
+        assert!(v[3].0.is_sign_positive() && v[3].1 == -2.0);
+        assert!(v[4].0.is_sign_positive() && v[4].1 == 1.0);
+        assert!(v[5].0.is_nan());
+        assert_eq!(Ordering::Less.then_float(&2.0f32, &1.0), Ordering::Less);
+        assert_eq!(Ordering::Equal.then_float(&-0.0f32, &0.0), Ordering::Less);
+    }
+
+    #[test]
+    fn test_cmp_ref() {
+        use super::cmp_ref;
+        use self::std::collections::BinaryHeap;
+        let v = [FloatOrd(2.0f64), FloatOrd(f64::NAN), FloatOrd(-1.0), FloatOrd(0.0),
+                 FloatOrd(-0.0)];
+        let mut heap: BinaryHeap<&FloatOrd<f64>> = v.iter().collect();
+        assert!(heap.pop().unwrap().is_nan());
+        assert_eq!(heap.pop(), Some(&FloatOrd(2.0)));
+        assert!(heap.pop().unwrap().0.is_sign_positive());
+        assert!(heap.pop().unwrap().0.is_sign_negative());
+        assert_eq!(heap.pop(), Some(&FloatOrd(-1.0)));
+        assert_eq!(heap.pop(), None);
+        assert_eq!(cmp_ref(&FloatOrd(-0.0f32), &FloatOrd(0.0)), Ordering::Less);
+        assert_eq!(cmp_ref(&v[1], &v[1]), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_cmp_slices() {
+        use super::cmp_slices;
+        assert_eq!(cmp_slices::<f64>(&[], &[]), Ordering::Equal);
+        assert_eq!(cmp_slices(&[], &[1.0f64]), Ordering::Less);
+        assert_eq!(cmp_slices(&[1.0f64, 2.0], &[1.0]), Ordering::Greater);
+        assert_eq!(cmp_slices(&[1.0f64, 2.0], &[1.0, 2.0]), Ordering::Equal);
+        assert_eq!(cmp_slices(&[1.0f32, -0.0], &[1.0, 0.0]), Ordering::Less);
+        assert_eq!(cmp_slices(&[2.0f64], &[1.0, 9.0]), Ordering::Greater);
+        assert_eq!(cmp_slices(&[1.0f64, f64::NAN, 0.0], &[1.0, f64::NAN, 1.0]), Ordering::Less);
+        assert_eq!(cmp_slices(&[1.0f64, f64::NAN], &[1.0, f64::INFINITY, 5.0]),
+                   Ordering::Greater);
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_range_bounds() {
+        use super::range_bounds;
+        use self::std::collections::BTreeMap;
+        let nan = f64::NAN;
+        let keys = [-nan, -f64::INFINITY, -1.0, -0.0, 0.0, 1.0, f64::INFINITY, nan];
+        let map: BTreeMap<_, _> = keys.iter().enumerate().map(|(i, &x)| (FloatOrd(x), i)).collect();
+        let range = |lo, hi| map.range(range_bounds(lo, hi)).map(|(_, &i)| i).collect::<Vec<_>>();
+        assert_eq!(range(-1.0, 1.0), [2, 3, 4, 5]);
+        assert_eq!(range(0.0, 1.0), [4, 5]);
+        assert_eq!(range(-1.0, -0.0), [2, 3]);
+        assert_eq!(range(-f64::INFINITY, f64::INFINITY), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(range(f64::INFINITY, f64::INFINITY), [6]);
+        assert_eq!(range(f64::INFINITY, nan), [6, 7]);
+        assert_eq!(range(-nan, -f64::INFINITY), [0, 1]);
+        assert_eq!(range(-nan, nan), [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert!(range(0.5, 0.75).is_empty());
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_min_max() {
+        use super::{min, max};
+        assert_eq!(min(1.0, 2.0), 1.0);
+        assert_eq!(max(1.0f32, 2.0), 2.0);
+        // f64::min and f64::max ignore NaN, but the total ordering does not.
+        assert_eq!(f64::NAN.max(1.0), 1.0);
+        assert!(max(f64::NAN, 1.0).is_nan());
+        assert_eq!(min(f64::NAN, 1.0), 1.0);
+        assert!(min(-f64::NAN, 1.0).is_nan());
+        assert_eq!(max(-f64::NAN, 1.0), 1.0);
+        // f64::min and f64::max may return either zero, but -0 < +0 here.
+        assert!(min(0.0f64, -0.0).is_sign_negative());
+        assert!(min(-0.0f64, 0.0).is_sign_negative());
+        assert!(max(0.0f32, -0.0).is_sign_positive());
+        assert!(max(-0.0f32, 0.0).is_sign_positive());
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_select_nth() {
+        let mut rng = thread_rng();
+        for n in 1..64 {
+            let v = rng.gen_iter::<f64>()
+                .map(|x| if x < 0.1 { f64::NAN } else if x < 0.2 { -f64::NAN } else { x - 0.6 })
+                .take(n)
+                .collect::<Vec<_>>();
+            let mut sorted = v.clone();
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            for k in 0..n {
+                let mut w = v.clone();
+                let x = *super::select_nth(&mut w, k);
+                assert_eq!(x.to_bits(), sorted[k].to_bits());
+                assert!(w[..k].iter().all(|&y| FloatOrd(y) <= FloatOrd(x)));
+                assert!(w[k + 1..].iter().all(|&y| FloatOrd(y) >= FloatOrd(x)));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_select_nth_out_of_bounds() {
+        super::select_nth(&mut [1.0f64, 2.0], 2);
+    }
+
+    #[test]
+    fn test_partition3() {
+        use super::partition3;
+        let mut rng = thread_rng();
+        let mut v = rng.gen_iter::<u8>()
+            .map(|x| f64::from(x % 7) - 3.0)
+            .take(300)
+            .collect::<Vec<_>>();
+        v.extend_from_slice(&[-0.0, -0.0, f64::NAN, 0.0]);
+        for &pivot in &[-3.0, 0.0, -0.0, 1.0, 10.0, f64::NAN] {
+            let (lt, gt) = partition3(&mut v, &pivot);
+            let p = FloatOrd(pivot);
+            assert!(v[..lt].iter().all(|&x| FloatOrd(x) < p));
+            assert!(v[lt..gt].iter().all(|&x| x.to_bits() == pivot.to_bits()));
+            assert!(v[gt..].iter().all(|&x| FloatOrd(x) > p));
+        }
+        let (lt, gt) = partition3(&mut v, &-0.0);
+        assert_eq!(gt - lt, 2);
+        assert_eq!(partition3(&mut [] as &mut [f32], &1.0), (0, 0));
+        assert_eq!(partition3(&mut [2.0f32, 2.0], &2.0), (0, 2));
+    }
+
+    #[test]
+    fn test_quantile() {
+        use super::quantile;
+        let mut rng = thread_rng();
+        for &n in &[1, 2, 10, 101] {
+            let mut v = rng.gen_iter::<f64>().take(n).collect::<Vec<_>>();
+            v[n / 2] = f64::NAN;
+            let mut sorted = v.clone();
+            sorted.sort_by_key(|&x| FloatOrd(x));
+            let at = |i: usize| FloatOrd(sorted[i]);
+            assert_eq!(quantile(&mut v, 0.0).map(FloatOrd), Some(at(0)));
+            assert_eq!(quantile(&mut v, 0.5).map(FloatOrd), Some(at(n.div_ceil(2) - 1)));
+            assert_eq!(quantile(&mut v, 1.0).map(FloatOrd), Some(at(n - 1)));
+            assert!(quantile(&mut v, 1.0).unwrap().is_nan());
+        }
+        let mut v = [4.0f32, 1.0, 3.0, 2.0];
+        assert_eq!(quantile(&mut v, 0.25), Some(1.0));
+        assert_eq!(quantile(&mut v, 0.26), Some(2.0));
+        assert_eq!(quantile(&mut v, 0.75), Some(3.0));
+        assert_eq!(quantile::<f64>(&mut [], 0.5), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_quantile_out_of_range() {
+        super::quantile(&mut [1.0f64], 1.5);
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_mode() {
+        use super::mode;
+        assert_eq!(mode(&mut [1.0, 4.0, 2.0, 4.0, 3.0, 4.0, 1.0]), Some(4.0));
+        assert_eq!(mode(&mut [7.0f32]), Some(7.0));
+        assert_eq!(mode::<f32>(&mut []), None);
+
+        // Signed zeros are counted separately.
+        let m = mode(&mut [0.0, -0.0, 1.0, -0.0f64]).unwrap();
+        assert!(m == 0.0 && m.is_sign_negative());
+        let m = mode(&mut [0.0, -0.0, 0.0, 1.0, -0.0, 0.0f64]).unwrap();
+        assert!(m == 0.0 && m.is_sign_positive());
+
+        // Ties go to the smallest value.
+        assert_eq!(mode(&mut [3.0, 2.0, 3.0, 2.0, f64::INFINITY]), Some(2.0));
+        let m = mode(&mut [0.0, -0.0f64]).unwrap();
+        assert!(m.is_sign_negative());
+        assert!(mode(&mut [f64::NAN, 1.0, f64::NAN]).unwrap().is_nan());
+    }
+
+    #[cfg(all(feature="alloc", not(feature="nan-at-top")))]
+    #[test]
+    fn test_mode_nan_payloads() {
+        let nan = f32::NAN;
+        let payload = f32::from_bits(nan.to_bits() | 1);
+        let m = super::mode(&mut [payload, nan, 1.0, payload, nan, payload]).unwrap();
+        assert_eq!(m.to_bits(), payload.to_bits());
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_median() {
+        use super::median;
+        let nan = f64::NAN;
+        assert_eq!(median::<f32>(&mut []), None);
+        assert_eq!(median(&mut [5.0]), Some(5.0));
+        assert_eq!(median(&mut [4.0, 1.0, -2.0, 8.0, 3.0]), Some(3.0));
+        assert_eq!(median(&mut [4.0f32, 1.0, -2.0, 8.0]), Some(2.5));
+        assert_eq!(median(&mut [-0.0, 0.0, 0.0]).map(f64::to_bits), Some(0.0f64.to_bits()));
+        // NaN sorts at the top...
+        assert_eq!(median(&mut [nan, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&mut [nan, 1.0, 2.0, 3.0]), Some(2.5));
+        // ...and becomes part of the median if there are enough of them.
+        assert!(median(&mut [nan, nan, 1.0]).unwrap().is_nan());
+        assert!(median(&mut [nan, nan, 1.0, 2.0]).unwrap().is_nan());
+        assert_eq!(median(&mut [-nan, 1.0, 2.0]), Some(1.0));
+        // The mean of the middle elements does not overflow.
+        assert_eq!(median(&mut [f64::MAX, f64::MAX]), Some(f64::MAX));
+        assert_eq!(median(&mut [f64::MAX, 1.0, f64::MAX / 2.0, f64::MAX]),
+                   Some(f64::MAX * 0.75));
+        assert_eq!(median(&mut [-f64::MAX, f64::MAX]), Some(0.0));
+        assert_eq!(median(&mut [-f32::MAX, -f32::MAX, 0.0, 1.0]), Some(-f32::MAX / 2.0));
+        assert_eq!(median(&mut [-0.0, -0.0]).map(f64::to_bits), Some((-0.0f64).to_bits()));
+        assert_eq!(median(&mut [f64::INFINITY, f64::INFINITY]), Some(f64::INFINITY));
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_is_sorted() {
+        use super::{is_sorted, is_sorted_descending};
+        let nan = f64::NAN;
+        assert!(is_sorted::<f64>(&[]));
+        assert!(is_sorted(&[1.0f32]));
+        assert!(is_sorted(&[-nan, -1.0, -0.0, 0.0, 0.0, 2.0, f64::INFINITY, nan]));
+        assert!(is_sorted(&[1.0, 2.0, nan]));
+        assert!(!is_sorted(&[1.0, nan, 2.0]));
+        assert!(!is_sorted(&[0.0, -0.0]));
+        assert!(!is_sorted(&[3.0f32, 1.0, 2.0]));
+        assert!(is_sorted_descending::<f64>(&[]));
+        assert!(is_sorted_descending(&[nan, f64::INFINITY, 2.0, 0.0, -0.0, -nan]));
+        assert!(!is_sorted_descending(&[2.0, nan]));
+        assert!(!is_sorted_descending(&[-0.0f32, 0.0]));
+        assert!(is_sorted_descending(&[1.0f32, 1.0]));
+    }
+
+    #[test]
+    fn test_is_strictly_sorted() {
+        use super::is_strictly_sorted;
+        let nan = f64::NAN;
+        assert!(is_strictly_sorted::<f64>(&[]));
+        assert!(is_strictly_sorted(&[1.0f32]));
+        assert!(is_strictly_sorted(&[-0.0, 0.0]));
+        assert!(!is_strictly_sorted(&[0.0, 0.0]));
+        assert!(!is_strictly_sorted(&[-0.0f32, -0.0]));
+        assert!(!is_strictly_sorted(&[0.0, -0.0]));
+        assert!(is_strictly_sorted(&[-1.0, 0.0, 2.0, f64::INFINITY, nan]));
+        assert!(!is_strictly_sorted(&[1.0, nan, nan]));
+        assert!(!is_strictly_sorted(&[1.0, nan, 2.0]));
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_binary_search() {
+        use super::binary_search;
+        let nan = f32::NAN;
+        let v = [-f32::INFINITY, -2.0, -0.0, 0.0, 3.0, f32::INFINITY, nan];
+        for (i, x) in v.iter().enumerate() {
+            assert_eq!(binary_search(&v, x), Ok(i));
+        }
+        assert_eq!(binary_search(&v, &-nan), Err(0));
+        assert_eq!(binary_search(&v, &-1.0), Err(2));
+        assert_eq!(binary_search(&v, &1.0), Err(4));
+        assert_eq!(binary_search(&[] as &[f32], &nan), Err(0));
+        // PartialOrd can't see the NaN, but the total ordering can.
+        assert_eq!(v.binary_search_by(|x| x.partial_cmp(&nan).unwrap_or(Ordering::Less)),
+                   Err(7));
+    }
+
+    #[test]
+    fn test_assert_total_order() {
+        use super::assert_total_order;
+        assert_total_order::<f64>(&[]);
+        assert_total_order(&[1.0f32]);
+        assert_total_order(&[-f64::INFINITY, -0.0, 0.0, 0.0, f64::INFINITY, f64::NAN]);
+        let err = self::std::panic::catch_unwind(|| assert_total_order(&[1.0, 2.0, 0.0, -0.0]))
+            .unwrap_err();
+        let msg = err.downcast_ref::<String>().unwrap();
+        assert_eq!(msg, "values are not in total order: \
+                         index 1 (FloatOrd(2.0)) sorts after index 2 (FloatOrd(+0.0))");
+    }
+
+    #[test]
+    #[should_panic(expected = "index 0 (FloatOrd(+0.0)) sorts after index 1 (FloatOrd(-0.0))")]
+    fn test_assert_total_order_zeros() {
+        super::assert_total_order(&[0.0f64, -0.0]);
+    }
+
+    #[test]
+    fn test_search_by_key() {
+        use super::search_by_key;
+        struct Reading {
+            value: f64,
+            id: u32,
+        }
+        let v = [Reading { value: -f64::INFINITY, id: 0 },
+                 Reading { value: -0.0, id: 1 },
+                 Reading { value: 0.0, id: 2 },
+                 Reading { value: 4.5, id: 3 },
+                 Reading { value: f64::NAN, id: 4 }];
+        for r in &v {
+            let i = search_by_key(&v, &r.value, |x| x.value).unwrap();
+            assert_eq!(v[i].id, r.id);
+        }
+        assert_eq!(search_by_key(&v, &1.0, |x| x.value), Err(3));
+        assert_eq!(search_by_key(&v, &f64::INFINITY, |x| x.value), Err(4));
+        assert_eq!(search_by_key(&v[..4], &f64::NAN, |x| x.value), Err(4));
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_partition_point() {
+        use super::partition_point;
+        let nan = f64::NAN;
+        let v = [-nan, -2.0, -0.0, 0.0, 1.5, f64::INFINITY, nan, nan];
+        let below = |t: f64| partition_point(&v, |&x| FloatOrd(x) < FloatOrd(t));
+        assert_eq!(below(-nan), 0);
+        assert_eq!(below(-f64::INFINITY), 1);
+        assert_eq!(below(0.0), 3);
+        assert_eq!(below(-0.0), 2);
+        assert_eq!(below(1.0), 4);
+        assert_eq!(below(nan), 6);
+        assert_eq!(partition_point(&v, |&x| FloatOrd(x) <= FloatOrd(nan)), 8);
+        assert_eq!(partition_point(&[] as &[f32], |_| true), 0);
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_bin_index() {
+        use super::bin_index;
+        let edges = [-10.0, -1.0, 0.0, 2.5, 100.0];
+        assert_eq!(bin_index(&edges, &-f64::INFINITY), 0);
+        assert_eq!(bin_index(&edges, &-10.5), 0);
+        assert_eq!(bin_index(&edges, &-10.0), 1);
+        assert_eq!(bin_index(&edges, &-5.0), 1);
+        assert_eq!(bin_index(&edges, &-0.0), 2);
+        assert_eq!(bin_index(&edges, &0.0), 3);
+        assert_eq!(bin_index(&edges, &1.0), 3);
+        assert_eq!(bin_index(&edges, &2.5), 4);
+        assert_eq!(bin_index(&edges, &1e9), 5);
+        assert_eq!(bin_index(&edges, &f64::NAN), 5);
+        assert_eq!(bin_index(&edges, &-f64::NAN), 0);
+        assert_eq!(bin_index(&[] as &[f32], &1.0), 0);
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_percentile_rank() {
+        use super::percentile_rank;
+        let nan = f64::NAN;
+        let v = [-nan, -1.0, -0.0, 0.0, 2.0, 2.0, 2.0, 5.0, nan, nan];
+        assert_eq!(percentile_rank(&v, &-f64::INFINITY), 0.1);
+        assert_eq!(percentile_rank(&v, &-1.0), 0.2);
+        assert_eq!(percentile_rank(&v, &-0.0), 0.3);
+        assert_eq!(percentile_rank(&v, &0.0), 0.4);
+        assert_eq!(percentile_rank(&v, &2.0), 0.7);
+        assert_eq!(percentile_rank(&v, &3.0), 0.7);
+        assert_eq!(percentile_rank(&v, &f64::INFINITY), 0.8);
+        assert_eq!(percentile_rank(&v, &nan), 1.0);
+        assert_eq!(percentile_rank(&v, &-nan), 0.1);
+        assert_eq!(percentile_rank(&v[1..8], &-2.0f64), 0.0);
+        assert!(percentile_rank(&[], &1.0f32).is_nan());
+    }
+
+    #[test]
+    fn test_count_less_than() {
+        use super::{count_less_than, bin_index};
+        let nan = f64::NAN;
+        let v = [-1.0, -0.0, 0.0, 2.0, 2.0, 2.0, 5.0, f64::INFINITY, nan, nan];
+        assert_eq!(count_less_than(&v, &-5.0), 0);
+        assert_eq!(count_less_than(&v, &-1.0), 0);
+        assert_eq!(count_less_than(&v, &0.0), 2);
+        assert_eq!(count_less_than(&v, &-0.0), 1);
+        assert_eq!(count_less_than(&v, &2.0), 3);
+        assert_eq!(bin_index(&v, &2.0), 6);
+        assert_eq!(count_less_than(&v, &3.0), 6);
+        assert_eq!(count_less_than(&v, &f64::INFINITY), 7);
+        assert_eq!(count_less_than(&v, &nan), 8);
+        assert_eq!(count_less_than::<f32>(&[], &1.0), 0);
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_slice_ext_search() {
+        use super::FloatSliceExt;
+        let nan = f64::NAN;
+        let v = [-nan, -1.0, -0.0, 0.0, 1.5, nan];
+        assert_eq!(v.float_binary_search(&-nan), Ok(0));
+        assert_eq!(v.float_binary_search(&-0.0), Ok(2));
+        assert_eq!(v.float_binary_search(&0.0), Ok(3));
+        assert_eq!(v.float_binary_search(&nan), Ok(5));
+        assert_eq!(v.float_binary_search(&-2.0), Err(1));
+        assert_eq!(v.float_binary_search(&f64::INFINITY), Err(5));
+    }
+
+    #[cfg(any(feature="pdqsort", feature="alloc"))]
+    #[test]
+    fn test_slice_ext_sort() {
+        use super::FloatSliceExt;
+        let nan = f64::NAN;
+        let mut v = [nan, 1.5, 0.0, -0.0, -1.0];
+        v.float_sort();
+        assert_eq!(v.float_binary_search(&-1.0), Ok(0));
+        assert!(v[1].is_sign_negative());
+        assert!(v[4].is_nan());
+        assert_eq!(v.float_binary_search(&0.0), Ok(2));
+        assert_eq!(v.float_binary_search(&nan), Ok(4));
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_iter_ext() {
+        use super::FloatIterExt;
+        assert_eq!(Vec::<f64>::new().into_iter().float_min(), None);
+        assert_eq!(Vec::<f32>::new().into_iter().float_max(), None);
+
+        let v = [3.0f64, -0.0, 0.0, 7.5, -2.0];
+        assert_eq!(v.iter().cloned().float_min(), Some(-2.0));
+        assert_eq!(v.iter().cloned().float_max(), Some(7.5));
+        assert!(v[1..3].iter().cloned().float_min().unwrap().is_sign_negative());
+        assert!(v[1..3].iter().cloned().float_max().unwrap().is_sign_positive());
+
+        let v = [3.0f32, f32::NAN, -f32::INFINITY, 1.0];
+        assert!(v.iter().cloned().float_max().unwrap().is_nan());
+        assert_eq!(v.iter().cloned().float_min(), Some(-f32::INFINITY));
+        let v = [3.0f32, -f32::NAN, 1.0];
+        assert!(v.iter().cloned().float_min().unwrap().is_nan());
+        assert_eq!(v.iter().cloned().float_max(), Some(3.0));
+    }
+
+    #[cfg(feature="rayon")]
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_par_sort() {
+        let nan = f64::NAN;
+        let mut v = [-1.0, 5.0, 0.0, -0.0, nan, 1.5, -nan, 3.7];
+        super::par_sort(&mut v);
+        assert!(v[0].is_nan() && v[0].is_sign_negative());
+        assert!(v[1] == -1.0);
+        assert!(v[2] == 0.0 && v[2].is_sign_negative());
+        assert!(v[3] == 0.0 && v[3].is_sign_positive());
+        assert!(v[7].is_nan() && v[7].is_sign_positive());
+
+        let mut rng = thread_rng();
+        let mut v = rng.gen_iter::<u64>()
+            .map(f64::from_bits)
+            .take(1 << 18)
+            .collect::<Vec<_>>();
+        let mut v1 = v.clone();
+        super::par_sort(&mut v);
+        v1.sort_by(|a, b| a.total_cmp(b));
+        assert!(v.iter().zip(&v1).all(|(a, b)| a.to_bits() == b.to_bits()));
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_radix_sort_nan() {
+        let nan = f64::NAN;
+        let mut v = [-1.0, 5.0, 0.0, -0.0, nan, 1.5, nan, 3.7];
+        super::radix_sort(&mut v);
+        assert!(v[0] == -1.0);
+        assert!(v[1] == 0.0 && v[1].is_sign_negative());
+        assert!(v[2] == 0.0 && !v[2].is_sign_negative());
+        assert!(v[3] == 1.5);
+        assert!(v[4] == 3.7);
+        assert!(v[5] == 5.0);
+        assert!(v[6].is_nan());
+        assert!(v[7].is_nan());
+
+        let mut v: [f32; 0] = [];
+        super::radix_sort(&mut v);
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_radix_sort_numbers() {
+        let mut rng = thread_rng();
+        for n in 0..12 {
+            let mut v = rng.gen_iter::<u64>()
+                .map(f64::from_bits)
+                .take(1 << n)
+                .collect::<Vec<_>>();
+            let mut v1 = v.clone();
+            super::radix_sort(&mut v);
+            super::sort_stable(&mut v1);
+            assert!(v.iter().zip(&v1).all(|(a, b)| a.to_bits() == b.to_bits()));
+
+            let mut v = rng.gen_iter::<f32>()
+                .map(|x| x - 0.5)
+                .take(1 << n)
+                .collect::<Vec<_>>();
+            let mut v1 = v.clone();
+            super::radix_sort(&mut v);
+            super::sort_stable(&mut v1);
+            assert!(v.iter().zip(&v1).all(|(a, b)| a.to_bits() == b.to_bits()));
+        }
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_dedup() {
+        let nan = f64::NAN;
+        let mut v = vec![-nan, -nan, -1.0, -0.0, -0.0, 0.0, 0.0, 0.0, 2.0, nan, nan, nan];
+        super::dedup(&mut v);
+        assert_eq!(v.len(), 6);
+        assert!(v[0].is_nan() && v[0].is_sign_negative());
+        assert_eq!(v[1], -1.0);
+        assert!(v[2] == 0.0 && v[2].is_sign_negative());
+        assert!(v[3] == 0.0 && v[3].is_sign_positive());
+        assert_eq!(v[4], 2.0);
+        assert!(v[5].is_nan() && v[5].is_sign_positive());
+
+        // NaN with different payloads are not equal.
+        let mut v = vec![f32::NAN, f32::from_bits(f32::NAN.to_bits() | 1)];
+        super::dedup(&mut v);
+        assert_eq!(v.len(), 2);
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_dedup_by_ulps() {
+        use super::dedup_by_ulps;
+        let one = FloatOrd(1.0f64);
+        let run = [one.0, one.next_up().0, one.next_up().next_up().0,
+                   one.next_up().next_up().next_up().0];
+        let mut v = run.to_vec();
+        dedup_by_ulps(&mut v, 2);
+        assert_eq!(v, [run[0], run[3]]);
+        let mut v = run.to_vec();
+        dedup_by_ulps(&mut v, 0);
+        assert_eq!(v, run);
+
+        // Clearly separated values are kept.
+        let mut v = vec![-1.0, -0.5, 0.0, 0.5, 1.0, f64::INFINITY];
+        dedup_by_ulps(&mut v, 2);
+        assert_eq!(v, [-1.0, -0.5, 0.0, 0.5, 1.0, f64::INFINITY]);
+
+        // Zeros are one step apart.
+        let mut v = vec![-0.0f32, 0.0];
+        dedup_by_ulps(&mut v, 1);
+        assert_eq!(v.len(), 1);
+        assert!(v[0].is_sign_negative());
+
+        // NaN is only merged with an equal NaN.
+        let nan = f32::NAN;
+        let mut v = vec![f32::MAX, f32::INFINITY, nan, nan];
+        dedup_by_ulps(&mut v, u64::MAX);
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0], f32::MAX);
+        assert!(v[1].is_nan());
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_argsort() {
+        use super::argsort;
+        let mut rng = thread_rng();
+        let mut v = rng.gen_iter::<f64>()
+            .map(|x| (x * 8.0).floor() - 4.0)
+            .take(200)
+            .collect::<Vec<_>>();
+        v[7] = f64::NAN;
+        v[50] = -0.0;
+        let w = v.clone();
+        let idx = argsort(&v);
+        assert!(v.iter().zip(&w).all(|(a, b)| a.to_bits() == b.to_bits()));
+        assert!(idx.windows(2).all(|i| {
+            let (a, b) = (FloatOrd(v[i[0]]), FloatOrd(v[i[1]]));
+            a < b || (a == b && i[0] < i[1])
+        }));
+        assert_eq!(*idx.last().unwrap(), 7);
+        assert_eq!(argsort(&[1.0f32, -0.0, 1.0, 0.0, 1.0]), [1, 3, 0, 2, 4]);
+        assert!(argsort::<f64>(&[]).is_empty());
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_top_k() {
+        use super::{top_k, MinFloat};
+        use self::std::cmp;
+        let mut rng = thread_rng();
+        for &n in &[0, 1, 10, 100] {
+            let mut v = rng.gen_iter::<f32>()
+                .map(|x| (x * 16.0).floor())
+                .take(n)
+                .collect::<Vec<_>>();
+            if n > 1 {
+                v[n / 2] = f32::NAN;
+                v[0] = f32::INFINITY;
+            }
+            let mut sorted = v.clone();
+            sorted.sort_by_key(|&x| MinFloat(x));
+            for &k in &[0, 1, 5, n, n + 3] {
+                let top = top_k(&v, k);
+                assert_eq!(top.len(), cmp::min(k, n));
+                assert!(top.iter().zip(&sorted).all(|(a, b)| a.to_bits() == b.to_bits()));
+            }
+        }
+        assert_eq!(top_k(&[1.0f64, -0.0, 0.0], 2)[1].to_bits(), 0.0f64.to_bits());
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_sort_by_cached_key() {
+        use self::std::cell::Cell;
+        let calls = Cell::new(0);
+        let mut v = (0..1000)
+            .map(|i| ((i * 7919 % 13) as f64 - 6.0, i))
+            .collect::<Vec<_>>();
+        v.push((f64::NAN, 1000));
+        v.push((-0.0, 1001));
+        super::sort_by_cached_key(&mut v, |x| {
+            calls.set(calls.get() + 1);
+            x.0
+        });
+        assert_eq!(calls.get(), 1002);
+        assert!(v.windows(2)
+                    .all(|w| FloatOrd(w[0].0) < FloatOrd(w[1].0) ||
+                             (FloatOrd(w[0].0) == FloatOrd(w[1].0) && w[0].1 < w[1].1)));
+        assert_eq!(v[1001].1, 1000);
+    }
+
+    #[test]
+    fn test_add() {
+        assert_eq!(FloatOrd(1.5) + FloatOrd(1.5), FloatOrd(1.5 + 1.5));
+        assert_eq!(1.5 + FloatOrd(1.5), FloatOrd(1.5 + 1.5));
+        assert_eq!(FloatOrd(1.5) + 1.5, FloatOrd(1.5 + 1.5));
+    }
+
+    #[test]
+    fn test_assign_ops() {
+        let mut a = FloatOrd(1.5f64);
+        a += FloatOrd(1.5);
+        assert_eq!(a, FloatOrd(3.0));
+        a += 1.0;
+        assert_eq!(a, FloatOrd(4.0));
+        a -= FloatOrd(0.5);
+        assert_eq!(a, FloatOrd(3.5));
+        a -= 1.5;
+        assert_eq!(a, FloatOrd(2.0));
+        a *= FloatOrd(3.0);
+        assert_eq!(a, FloatOrd(6.0));
+        a *= 2.0;
+        assert_eq!(a, FloatOrd(12.0));
+        a /= FloatOrd(4.0);
+        assert_eq!(a, FloatOrd(3.0));
+        a /= 2.0;
+        assert_eq!(a, FloatOrd(1.5));
+        a %= FloatOrd(1.0);
+        assert_eq!(a, FloatOrd(0.5));
+        a %= 0.5;
+        assert_eq!(a, FloatOrd(0.0));
+
+        let mut b = FloatOrd(1.5f32);
+        b += FloatOrd(1.5);
+        assert_eq!(b, FloatOrd(3.0));
+        b += 1.0;
+        assert_eq!(b, FloatOrd(4.0));
+        b -= FloatOrd(0.5);
+        assert_eq!(b, FloatOrd(3.5));
+        b -= 1.5;
+        assert_eq!(b, FloatOrd(2.0));
+        b *= FloatOrd(3.0);
+        assert_eq!(b, FloatOrd(6.0));
+        b *= 2.0;
+        assert_eq!(b, FloatOrd(12.0));
+        b /= FloatOrd(4.0);
+        assert_eq!(b, FloatOrd(3.0));
+        b /= 2.0;
+        assert_eq!(b, FloatOrd(1.5));
+        b %= FloatOrd(1.0);
+        assert_eq!(b, FloatOrd(0.5));
+        b %= 0.5;
+        assert_eq!(b, FloatOrd(0.0));
+    }
+
+    #[test]
+    fn test_new_finite() {
+        assert_eq!(FloatOrd::<f64>::new_finite(f64::NAN), None);
+        assert_eq!(FloatOrd::<f32>::new_finite(f32::NAN), None);
+        assert_eq!(FloatOrd::<f64>::new_finite(f64::INFINITY), Some(FloatOrd(f64::INFINITY)));
+        assert_eq!(FloatOrd::<f32>::new_finite(-0.0), Some(FloatOrd(-0.0f32)));
+        assert_eq!(FloatOrd::<f64>::try_new(f64::NAN), Err(super::NanError));
+        assert_eq!(FloatOrd::<f32>::try_new(1.5), Ok(FloatOrd(1.5f32)));
+        assert_eq!(super::NanError.to_string(), "value is NaN");
+    }
+
+    #[test]
+    fn test_from() {
+        let x: FloatOrd<f64> = 1.5.into();
+        assert_eq!(x, FloatOrd(1.5));
+        let v: Vec<FloatOrd<f32>> = [1.0f32, 2.0].iter().cloned().map(Into::into).collect();
+        assert_eq!(v, [FloatOrd(1.0), FloatOrd(2.0)]);
+        for &f in &[-0.0f64, 0.0, f64::NAN, -f64::NAN] {
+            let g: f64 = FloatOrd::from(f).into();
+            assert_eq!(f.to_bits(), g.to_bits());
+        }
+        for &f in &[-0.0f32, 0.0, f32::NAN, -f32::NAN] {
+            let g: f32 = FloatOrd::from(f).into();
+            assert_eq!(f.to_bits(), g.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_total_cmp() {
+        let grid = [-f64::NAN, -f64::INFINITY, -f64::MAX, -1.0, -f64::MIN_POSITIVE,
+                    -1e-310, -0.0, 0.0, 1e-310, f64::MIN_POSITIVE, 1.0, f64::MAX,
+                    f64::INFINITY, f64::NAN];
+        for a in &grid {
+            for b in &grid {
+                assert_eq!(FloatOrd(*a).total_cmp(&FloatOrd(*b)), a.total_cmp(b));
+                assert_eq!(FloatOrd(*a as f32).total_cmp(&FloatOrd(*b as f32)),
+                           (*a as f32).total_cmp(&(*b as f32)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_str() {
+        let nan: FloatOrd<f64> = "nan".parse().unwrap();
+        let neg_inf: FloatOrd<f64> = "-inf".parse().unwrap();
+        let neg_zero: FloatOrd<f64> = "-0".parse().unwrap();
+        let one: FloatOrd<f32> = "1.5".parse().unwrap();
+        assert!(nan.is_nan());
+        assert_eq!(neg_inf, FloatOrd(-f64::INFINITY));
+        assert!(neg_zero.is_sign_negative());
+        assert_eq!(one, FloatOrd(1.5));
+        assert!(neg_inf < neg_zero);
+        assert!(neg_zero < FloatOrd(0.0));
+        assert!(FloatOrd(f64::INFINITY) < nan);
+        assert!("abc".parse::<FloatOrd<f64>>().is_err());
+        assert_eq!("".parse::<FloatOrd<f32>>().unwrap_err(), "".parse::<f32>().unwrap_err());
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_predicates() {
+        let nan = FloatOrd(f64::NAN);
+        assert!(nan.is_nan() && !nan.is_finite() && !nan.is_infinite());
+        assert!(!nan.is_sign_negative());
+        assert!((-nan).is_nan() && (-nan).is_sign_negative());
+        let inf = FloatOrd(f32::INFINITY);
+        assert!(!inf.is_nan() && !inf.is_finite() && inf.is_infinite());
+        assert!(!inf.is_sign_negative() && (-inf).is_sign_negative());
+        let zero = FloatOrd(0.0f64);
+        assert!(!zero.is_nan() && zero.is_finite() && !zero.is_infinite());
+        assert!(!zero.is_sign_negative() && (-zero).is_sign_negative());
+        assert!(FloatOrd(-0.0f32).is_finite());
+        // Exactly the values with the sign bit set sort below +0.
+        for &x in &[-f64::NAN, -f64::INFINITY, -1.0, -0.0, 0.0, 1.0, f64::NAN] {
+            assert_eq!(FloatOrd(x).is_sign_negative(), FloatOrd(x) < zero);
+        }
+    }
+
+    #[test]
+    fn test_total_ord() {
+        use super::TotalOrd;
+        #[derive(Debug)]
+        struct Point {
+            x: f64,
+            y: f32,
+        }
+        impl PartialEq for Point {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+        impl Eq for Point {}
+        impl PartialOrd for Point {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Point {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.x.total_ordering(&other.x).then_with(|| self.y.total_ordering(&other.y))
+            }
+        }
+        let mut v = [
+            Point { x: 1.0, y: 2.0 },
+            Point { x: f64::NAN, y: 0.0 },
+            Point { x: 1.0, y: -0.0 },
+            Point { x: -3.0, y: f32::NAN },
+            Point { x: 1.0, y: 0.0 },
+        ];
+        v.sort();
+        assert_eq!(v[0].x, -3.0);
+        assert!(v[1].x == 1.0 && v[1].y.is_sign_negative());
+        assert!(v[2].x == 1.0 && v[2].y == 0.0 && v[2].y.is_sign_positive());
+        assert!(v[3].x == 1.0 && v[3].y == 2.0);
+        assert!(v[4].x.is_nan());
+        assert_eq!(Point { x: f64::NAN, y: 1.0 }, Point { x: f64::NAN, y: 1.0 });
+        assert_eq!(FloatOrd(1.0f64).total_ordering(&FloatOrd(-0.0)), Ordering::Greater);
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_reverse_cmp() {
+        let mut rng = thread_rng();
+        for _ in 0..1000 {
+            let a = FloatOrd(f64::from_bits(rng.gen()));
+            let b = FloatOrd(f64::from_bits(rng.gen()));
+            assert_eq!(a.reverse_cmp(&b), b.cmp(&a));
+            assert_eq!(a.reverse_cmp(&a), Ordering::Equal);
+        }
+        let nan = f32::NAN;
+        let mut v = [1.0, -0.0, nan, 0.0, -nan, 5.0].iter()
+            .map(|&x| FloatOrd(x))
+            .collect::<Vec<_>>();
+        v.sort_by(FloatOrd::<f32>::reverse_cmp);
+        assert!(v[0].is_nan() && !v[0].is_sign_negative());
+        assert_eq!(v[1], FloatOrd(5.0));
+        assert_eq!(v[2], FloatOrd(1.0));
+        assert_eq!(v[3], FloatOrd(0.0));
+        assert_eq!(v[4], FloatOrd(-0.0));
+        assert!(v[5].is_nan() && v[5].is_sign_negative());
+    }
+
+    #[test]
+    fn test_ieee_partial_cmp() {
+        let nan = FloatOrd(f64::NAN);
+        assert_eq!(nan.ieee_partial_cmp(&nan), None);
+        assert_eq!(nan.ieee_partial_cmp(&FloatOrd(1.0)), None);
+        assert_eq!(FloatOrd(1.0f64).ieee_partial_cmp(&nan), None);
+        assert_eq!(FloatOrd(-f32::NAN).ieee_partial_cmp(&FloatOrd(f32::INFINITY)), None);
+        assert_eq!(FloatOrd(1.0f64).ieee_partial_cmp(&FloatOrd(2.0)), Some(Ordering::Less));
+        assert_eq!(FloatOrd(2.0f32).ieee_partial_cmp(&FloatOrd(-1.0)),
+                   Some(Ordering::Greater));
+        assert_eq!(FloatOrd(-0.0f64).ieee_partial_cmp(&FloatOrd(0.0)), Some(Ordering::Equal));
+        assert_eq!(FloatOrd(-f64::INFINITY).ieee_partial_cmp(&FloatOrd(f64::MIN)),
+                   Some(Ordering::Less));
+        // The total ordering is unaffected.
+        assert_eq!(nan.partial_cmp(&nan), Some(Ordering::Equal));
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_clamp() {
+        let (lo, hi) = (FloatOrd(-1.0f64), FloatOrd(1.0f64));
+        assert_eq!(FloatOrd(0.5f64).clamp(lo, hi), FloatOrd(0.5));
+        assert_eq!(FloatOrd(-3.0f64).clamp(lo, hi), lo);
+        assert_eq!(FloatOrd(3.0f64).clamp(lo, hi), hi);
+        assert_eq!(FloatOrd(f64::NAN).clamp(lo, hi), hi);
+        assert_eq!(FloatOrd(-f64::NAN).clamp(lo, hi), lo);
+        assert!(FloatOrd(0.0f32).clamp(FloatOrd(-1.0), FloatOrd(-0.0)).is_sign_negative());
+        assert_eq!(FloatOrd(1.0f32).clamp(FloatOrd(2.0), FloatOrd(f32::NAN)), FloatOrd(2.0));
+        assert!(FloatOrd(f32::INFINITY)
+                    .clamp(FloatOrd(f32::NAN), FloatOrd(f32::NAN))
+                    .is_nan());
+    }
+
+    #[test]
+    fn test_clamp_to_finite() {
+        let scrub = |x: f64| FloatOrd(x).clamp_to_finite(0.0, f64::MIN, f64::MAX);
+        assert_eq!(scrub(f64::NAN), FloatOrd(0.0));
+        assert_eq!(scrub(-f64::NAN), FloatOrd(0.0));
+        assert_eq!(scrub(-f64::INFINITY), FloatOrd(f64::MIN));
+        assert_eq!(scrub(f64::INFINITY), FloatOrd(f64::MAX));
+        assert_eq!(scrub(-2.5), FloatOrd(-2.5));
+        assert!(scrub(-0.0).is_sign_negative());
+        assert_eq!(FloatOrd(f32::INFINITY).clamp_to_finite(0.0, -1.0, 1.0), FloatOrd(1.0f32));
+        assert_eq!(FloatOrd(f32::MAX).clamp_to_finite(0.0, -1.0, 1.0), FloatOrd(f32::MAX));
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(FloatOrd(-0.0f64).canonicalize(), FloatOrd(0.0));
+        assert!(FloatOrd(-0.0f32).canonicalize().0.is_sign_positive());
+        assert_eq!(hash(FloatOrd(-0.0f64).canonicalize()), hash(FloatOrd(0.0f64).canonicalize()));
+        let nans = [f64::NAN, -f64::NAN, f64::from_bits(f64::NAN.to_bits() | 1),
+                    f64::from_bits(!0)];
+        for &x in &nans {
+            let c = FloatOrd(x).canonicalize();
+            assert_eq!(c.0.to_bits(), f64::NAN.to_bits());
+            assert_eq!(hash(c), hash(FloatOrd(f64::NAN)));
+        }
+        assert_eq!(FloatOrd(-f32::NAN).canonicalize().0.to_bits(), f32::NAN.to_bits());
+        for &x in &[-1.5f64, 0.0, f64::INFINITY, -f64::INFINITY, f64::MIN_POSITIVE] {
+            assert_eq!(FloatOrd(x).canonicalize().0.to_bits(), x.to_bits());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_clamp_panics() {
+        FloatOrd(0.0f64).clamp(FloatOrd(f64::NAN), FloatOrd(0.0));
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_cmp_raw() {
+        assert!(FloatOrd(1.0f64) == 1.0);
+        assert!(FloatOrd(1.0f32) < 2.0);
+        assert!(FloatOrd(3.0f64) > -f64::INFINITY);
+        assert!(FloatOrd(-0.0f64) != 0.0);
+        assert!(FloatOrd(-0.0f32) < 0.0);
+        assert!(FloatOrd(0.0f64) > -0.0);
+        assert!(FloatOrd(f64::NAN) == f64::NAN);
+        assert!(FloatOrd(f32::NAN) > f32::INFINITY);
+        assert!(FloatOrd(-f64::NAN) < -f64::INFINITY);
+        assert!(FloatOrd(-f64::NAN) != f64::NAN);
+    }
+
+    #[test]
+    fn test_try_from_key_bytes() {
+        use self::std::convert::TryFrom;
+        use super::KeyLengthError;
+        let mut buf = Vec::new();
+        for &x in &[-f64::INFINITY, -1.5, -0.0, 0.0, 2.0, f64::NAN] {
+            buf.extend_from_slice(&FloatOrd(x).to_be_key_bytes());
+        }
+        let decoded = buf.chunks(8)
+            .map(|c| FloatOrd::<f64>::try_from(c).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(decoded.len(), 6);
+        assert!(decoded[2].0.is_sign_negative() && decoded[3].0.is_sign_positive());
+        assert!(decoded[5].is_nan());
+        assert_eq!(decoded[1], FloatOrd(-1.5));
+        assert_eq!(FloatOrd::<f64>::try_from(&buf[..7]), Err(KeyLengthError));
+        assert_eq!(FloatOrd::<f64>::try_from(&buf[..0]), Err(KeyLengthError));
+
+        let bytes = FloatOrd(0.25f32).to_be_key_bytes();
+        assert_eq!(FloatOrd::<f32>::try_from(&bytes[..]), Ok(FloatOrd(0.25)));
+        assert_eq!(FloatOrd::<f32>::try_from(&buf[..8]), Err(KeyLengthError));
+        assert_eq!(KeyLengthError.to_string(), "key has the wrong length");
+    }
+
+    #[test]
+    fn test_raw_bits() {
+        let payload = f64::NAN.to_bits() | 0x1234;
+        let x = FloatOrd::<f64>::from_bits(payload);
+        assert!(x.is_nan());
+        assert_eq!(x.to_bits(), payload);
+        assert!(x > FloatOrd(f64::INFINITY));
+        assert_eq!(FloatOrd::<f64>::from_bits(x.to_bits()).to_ord_bits(), x.to_ord_bits());
+        assert_ne!(x.to_bits(), x.to_ord_bits());
+
+        let y = FloatOrd::<f32>::from_bits(!0);
+        assert!(y.is_nan() && y.is_sign_negative());
+        assert_eq!(y.to_bits(), !0);
+        assert_eq!(FloatOrd(-1.5f32).to_bits(), (-1.5f32).to_bits());
+    }
+
+    #[test]
+    fn test_be_key_bytes() {
+        let mut rng = thread_rng();
+        let v = rng.gen_iter::<u64>()
+            .map(f64::from_bits)
+            .take(4096)
+            .collect::<Vec<_>>();
+        for w in v.windows(2) {
+            let (a, b) = (FloatOrd(w[0]), FloatOrd(w[1]));
+            assert_eq!(a.to_be_key_bytes().cmp(&b.to_be_key_bytes()), a.cmp(&b));
+            assert_eq!(FloatOrd::<f64>::from_be_key_bytes(a.to_be_key_bytes()), a);
+        }
+        let v = rng.gen_iter::<u32>()
+            .map(f32::from_bits)
+            .take(4096)
+            .collect::<Vec<_>>();
+        for w in v.windows(2) {
+            let (a, b) = (FloatOrd(w[0]), FloatOrd(w[1]));
+            assert_eq!(a.to_be_key_bytes()[..].cmp(&b.to_be_key_bytes()[..]), a.cmp(&b));
+            assert_eq!(FloatOrd::<f32>::from_be_key_bytes(a.to_be_key_bytes()), a);
+        }
+        assert_eq!(FloatOrd(0.0f32).to_be_key_bytes(), [0x80, 0, 0, 0]);
+        assert_eq!(FloatOrd(-0.0f32).to_be_key_bytes(), [0x7f, 0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_ord_bits() {
+        let mut rng = thread_rng();
+        let specials = [-f64::NAN, -f64::INFINITY, -1.0, -0.0, 0.0, 1.0, f64::INFINITY,
+                        f64::NAN];
+        let v = rng.gen_iter::<u64>()
+            .map(f64::from_bits)
+            .take(4096)
+            .chain(specials.iter().cloned())
+            .collect::<Vec<_>>();
+        for &x in &v {
+            let bits = FloatOrd(x).to_ord_bits();
+            assert_eq!(FloatOrd::<f64>::from_ord_bits(bits).0.to_bits(), x.to_bits());
+        }
+        assert!(v.windows(2)
+                    .all(|w| FloatOrd(w[0]).cmp(&FloatOrd(w[1])) ==
+                             FloatOrd(w[0]).to_ord_bits().cmp(&FloatOrd(w[1]).to_ord_bits())));
+
+        let v = rng.gen_iter::<u32>()
+            .map(f32::from_bits)
+            .take(4096)
+            .chain(specials.iter().map(|&x| x as f32))
+            .collect::<Vec<_>>();
+        for &x in &v {
+            let bits = FloatOrd(x).to_ord_bits();
+            assert_eq!(FloatOrd::<f32>::from_ord_bits(bits).0.to_bits(), x.to_bits());
+        }
+        assert!(v.windows(2)
+                    .all(|w| FloatOrd(w[0]).cmp(&FloatOrd(w[1])) ==
+                             FloatOrd(w[0]).to_ord_bits().cmp(&FloatOrd(w[1]).to_ord_bits())));
+
+        assert_eq!(FloatOrd(-0.0f64).to_ord_bits() + 1, FloatOrd(0.0f64).to_ord_bits());
+        assert_eq!(FloatOrd(-f32::NAN).to_ord_bits(), !(f32::NAN.to_bits() | 1 << 31));
+    }
+
+    #[cfg(feature="num-traits")]
+    #[test]
+    fn test_num_traits() {
+        use num_traits::{One, Zero};
+        assert_eq!(FloatOrd::<f64>::zero(), FloatOrd(0.0));
+        assert!(FloatOrd::<f32>::zero().is_sign_positive());
+        assert_eq!(FloatOrd::<f32>::one(), FloatOrd(1.0));
+        assert!(FloatOrd(0.0f64).is_zero());
+        assert!(FloatOrd(-0.0f64).is_zero());
+        assert!(FloatOrd(-0.0f32).is_zero());
+        assert!(FloatOrd(-0.0f64) < FloatOrd::<f64>::zero());
+        assert!(!FloatOrd(f64::NAN).is_zero());
+        assert!(!FloatOrd(1e-310f64).is_zero());
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_cmp_matches_ord_bits() {
+        // Whichever way `Ord` is implemented, it agrees with the integer keys.
+        let grid = [-f64::NAN, -f64::INFINITY, -f64::MAX, -1.0, -1e-310, -0.0, 0.0,
+                    1e-310, 1.0, f64::MAX, f64::INFINITY, f64::NAN];
+        for a in &grid {
+            for b in &grid {
+                let (a, b) = (FloatOrd(*a), FloatOrd(*b));
+                assert_eq!(a.cmp(&b), a.to_ord_bits().cmp(&b.to_ord_bits()));
+                assert_eq!(a.cmp(&b), a.0.total_cmp(&b.0));
+                let (a, b) = (FloatOrd(a.0 as f32), FloatOrd(b.0 as f32));
+                assert_eq!(a.cmp(&b), a.to_ord_bits().cmp(&b.to_ord_bits()));
+                assert_eq!(a.cmp(&b), a.0.total_cmp(&b.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sum_product() {
+        let v = [1.5f64, -2.25, 4.0, 1e-3];
+        let w = v.iter().cloned().map(FloatOrd).collect::<Vec<_>>();
+        assert_eq!(w.iter().cloned().sum::<FloatOrd<f64>>(), FloatOrd(v.iter().sum::<f64>()));
+        assert_eq!(w.iter().sum::<FloatOrd<f64>>(), FloatOrd(v.iter().sum::<f64>()));
+        assert_eq!(w.iter().cloned().product::<FloatOrd<f64>>(),
+                   FloatOrd(v.iter().product::<f64>()));
+        assert_eq!(w.iter().product::<FloatOrd<f64>>(), FloatOrd(v.iter().product::<f64>()));
+
+        let v = [0.5f32, 3.0, -1.0];
+        let w = [FloatOrd(0.5f32), FloatOrd(3.0), FloatOrd(-1.0)];
+        assert_eq!(w.iter().sum::<FloatOrd<f32>>(), FloatOrd(v.iter().sum::<f32>()));
+        assert_eq!(w.iter().product::<FloatOrd<f32>>(), FloatOrd(v.iter().product::<f32>()));
+
+        let empty: [FloatOrd<f64>; 0] = [];
+        assert_eq!(empty.iter().product::<FloatOrd<f64>>(), FloatOrd(1.0));
+        assert_eq!(empty.iter().sum::<FloatOrd<f64>>(), FloatOrd(0.0));
+        assert_eq!(empty.iter().cloned().sum::<FloatOrd<f64>>(), FloatOrd(0.0));
+        assert_eq!(empty.iter().cloned().product::<FloatOrd<f64>>(), FloatOrd(1.0));
+        assert!([FloatOrd(1.0), FloatOrd(f64::NAN)].iter().sum::<FloatOrd<f64>>().is_nan());
+    }
+
+    #[test]
+    fn test_next_up_down() {
+        let zero = FloatOrd(0.0f64);
+        let neg_zero = FloatOrd(-0.0f64);
+        let sub = FloatOrd(f64::from_bits(1));
+        assert_eq!(zero.next_up(), sub);
+        assert_eq!(sub.next_down(), zero);
+        assert_eq!(zero.next_down(), neg_zero);
+        assert_eq!(neg_zero.next_up(), zero);
+        assert_eq!(neg_zero.next_down(), -sub);
+        assert_eq!(FloatOrd(1.0f64).next_up(), FloatOrd(1.0 + f64::EPSILON));
+        assert_eq!(FloatOrd(f64::MAX).next_up(), FloatOrd(f64::INFINITY));
+        assert_eq!(FloatOrd(f64::INFINITY).next_down(), FloatOrd(f64::MAX));
+        assert_eq!(FloatOrd(-f64::MAX).next_down(), FloatOrd(-f64::INFINITY));
+        assert!(FloatOrd(f64::INFINITY).next_up().is_nan());
+        assert!(FloatOrd(-f64::INFINITY).next_down().is_nan());
+        let top = FloatOrd::<f64>::from_ord_bits(u64::MAX);
+        assert_eq!(top.next_up(), top);
+        let bottom = FloatOrd::<f64>::from_ord_bits(0);
+        assert_eq!(bottom.next_down(), bottom);
 
-    use self::rand::{Rng, thread_rng};
-    use self::std::prelude::v1::*;
-    use self::std::collections::hash_map::DefaultHasher;
-    use self::std::hash::{Hash, Hasher};
-    use self::std::f64;
-    use super::FloatOrd;
+        assert_eq!(FloatOrd(0.0f32).next_up(), FloatOrd(f32::from_bits(1)));
+        assert_eq!(FloatOrd(0.0f32).next_down(), FloatOrd(-0.0f32));
+        assert_eq!(FloatOrd(f32::MAX).next_up(), FloatOrd(f32::INFINITY));
+        assert_eq!(FloatOrd(1.0f32).next_down().next_up(), FloatOrd(1.0f32));
+    }
 
     #[test]
-    fn test_ord() {
-        assert!(FloatOrd(1.0f64) < FloatOrd(2.0f64));
-        assert!(FloatOrd(2.0f32) > FloatOrd(1.0f32));
-        assert!(FloatOrd(1.0f64) == FloatOrd(1.0f64));
-        assert!(FloatOrd(1.0f32) == FloatOrd(1.0f32));
-        assert!(FloatOrd(0.0f64) > FloatOrd(-0.0f64));
-        assert!(FloatOrd(0.0f32) > FloatOrd(-0.0f32));
-        assert!(FloatOrd(::core::f64::NAN) == FloatOrd(::core::f64::NAN));
-        assert!(FloatOrd(::core::f32::NAN) == FloatOrd(::core::f32::NAN));
-        assert!(FloatOrd(-::core::f64::NAN) < FloatOrd(::core::f64::NAN));
-        assert!(FloatOrd(-::core::f32::NAN) < FloatOrd(::core::f32::NAN));
-        assert!(FloatOrd(-::core::f64::INFINITY) < FloatOrd(::core::f64::INFINITY));
-        assert!(FloatOrd(-::core::f32::INFINITY) < FloatOrd(::core::f32::INFINITY));
-        assert!(FloatOrd(::core::f64::INFINITY) < FloatOrd(::core::f64::NAN));
-        assert!(FloatOrd(::core::f32::INFINITY) < FloatOrd(::core::f32::NAN));
-        assert!(FloatOrd(-::core::f64::NAN) < FloatOrd(::core::f64::INFINITY));
-        assert!(FloatOrd(-::core::f32::NAN) < FloatOrd(::core::f32::INFINITY));
+    fn test_within_ulps() {
+        let one = FloatOrd(1.0f64);
+        assert!(one.within_ulps(one, 0));
+        assert!(one.within_ulps(one.next_up(), 1));
+        assert!(!one.within_ulps(one.next_up(), 0));
+        assert!(one.next_up().next_up().within_ulps(one, 2));
+        assert!(!one.next_up().next_up().within_ulps(one, 1));
+        let sub = FloatOrd(f32::from_bits(1));
+        assert!(FloatOrd(-0.0f32).within_ulps(FloatOrd(0.0), 1));
+        assert!(!FloatOrd(-0.0f32).within_ulps(FloatOrd(0.0), 0));
+        assert!((-sub).within_ulps(sub, 3));
+        assert!(!sub.within_ulps(-sub, 2));
+        assert!(FloatOrd(f64::MAX).within_ulps(FloatOrd(f64::INFINITY), 1));
+        assert!(FloatOrd(-f64::MAX).within_ulps(FloatOrd(f64::MAX), u64::MAX));
+        let nan = FloatOrd(f64::NAN);
+        assert!(!nan.within_ulps(nan, u64::MAX));
+        assert!(!nan.within_ulps(FloatOrd(f64::INFINITY), u64::MAX));
+        assert!(!FloatOrd(1.0f32).within_ulps(FloatOrd(-f32::NAN), u64::MAX));
     }
 
     #[test]
-    fn test_ord_numbers() {
-        let mut rng = thread_rng();
-        for n in 0..16 {
-            for l in 0..16 {
-                let v = rng.gen_iter::<f64>()
-                    .map(|x| x % (1 << l) as i64 as f64)
-                    .take((1 << n))
-                    .collect::<Vec<_>>();
-                assert!(v.windows(2)
-                            .all(|w| (w[0] <= w[1]) == (FloatOrd(w[0]) <= FloatOrd(w[1]))));
-            }
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_classify_ord() {
+        use super::FloatClass::*;
+        // 1e-40 is subnormal as an f32.
+        let cases = [(-f64::NAN, NegativeNan), (-f64::INFINITY, NegativeInfinity),
+                     (-1e30, Negative), (-1.0, Negative), (-1e-40, Negative),
+                     (-0.0, NegativeZero), (0.0, PositiveZero), (1e-40, Positive),
+                     (1.0, Positive), (1e30, Positive),
+                     (f64::INFINITY, PositiveInfinity), (f64::NAN, PositiveNan)];
+        for &(x, class) in &cases {
+            assert_eq!(FloatOrd(x).classify_ord(), class);
+            assert_eq!(FloatOrd(x as f32).classify_ord(), class);
         }
+        assert_eq!(FloatOrd(f64::from_bits(1)).classify_ord(), Positive);
+        assert_eq!(FloatOrd(-f64::MAX).classify_ord(), Negative);
+        // The classes are in the same order as the values.
+        assert!(cases.windows(2).all(|w| {
+            (FloatOrd(w[0].0) < FloatOrd(w[1].0)) && w[0].1 <= w[1].1
+        }));
     }
 
-    fn hash<F: Hash>(f: F) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        f.hash(&mut hasher);
-        hasher.finish()
+    #[test]
+    fn test_cmp_mixed_width() {
+        assert!(FloatOrd(1.5f32) == FloatOrd(1.5f64));
+        assert!(FloatOrd(1.5f64) == FloatOrd(1.5f32));
+        assert!(FloatOrd(0.1f32) != FloatOrd(0.1f64));
+        assert!(FloatOrd(0.1f32) > FloatOrd(0.1f64));
+        assert!(FloatOrd(0.1f64) < FloatOrd(0.1f32));
+        assert!(FloatOrd(-0.0f32) < FloatOrd(0.0f64));
+        assert!(FloatOrd(f32::MAX) < FloatOrd(f64::MAX));
+        assert!(FloatOrd(f32::INFINITY) == FloatOrd(f64::INFINITY));
+        assert!(FloatOrd(-f32::INFINITY) < FloatOrd(f64::MIN));
+        assert!(FloatOrd(f32::NAN) == FloatOrd(f64::NAN));
+        assert!(FloatOrd(f32::NAN) > FloatOrd(f64::INFINITY));
+        assert!(FloatOrd(f64::INFINITY) < FloatOrd(f32::NAN));
     }
 
     #[test]
-    fn test_hash() {
-        assert_ne!(hash(FloatOrd(0.0f64)), hash(FloatOrd(-0.0f64)));
-        assert_ne!(hash(FloatOrd(0.0f32)), hash(FloatOrd(-0.0f32)));
-        assert_eq!(hash(FloatOrd(-0.0f64)), hash(FloatOrd(-0.0f64)));
-        assert_eq!(hash(FloatOrd(0.0f32)), hash(FloatOrd(0.0f32)));
-        assert_ne!(hash(FloatOrd(::core::f64::NAN)),
-                   hash(FloatOrd(-::core::f64::NAN)));
-        assert_ne!(hash(FloatOrd(::core::f32::NAN)),
-                   hash(FloatOrd(-::core::f32::NAN)));
-        assert_eq!(hash(FloatOrd(::core::f64::NAN)),
-                   hash(FloatOrd(::core::f64::NAN)));
-        assert_eq!(hash(FloatOrd(-::core::f32::NAN)),
-                   hash(FloatOrd(-::core::f32::NAN)));
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_cmp_mixed_width_nan() {
+        let neg = FloatOrd(-f32::NAN);
+        assert!(neg == FloatOrd(-f64::NAN));
+        assert!(FloatOrd(-f64::NAN) == neg);
+        assert!(neg != FloatOrd(f64::NAN));
+        assert!(neg < FloatOrd(f64::NEG_INFINITY));
+        assert!(FloatOrd(f64::NEG_INFINITY) > neg);
+        assert!(FloatOrd(f64::NAN) > neg);
+
+        // NaN payloads are widened exactly, so equality stays transitive.
+        let a = FloatOrd(f32::from_bits(0x7fc0_0001));
+        let b = FloatOrd(f32::NAN);
+        let c = FloatOrd(f64::NAN);
+        assert!(a > b);
+        assert!(b == c);
+        assert!(a != c);
+        assert!(a > c);
+        assert!(c < a);
+        assert!(a == FloatOrd(f64::from_bits(0x7ff8_0000_2000_0000)));
+        let d = FloatOrd(-f32::from_bits(0x7fc0_0001));
+        assert!(d < neg && d < FloatOrd(-f64::NAN));
     }
 
-    #[cfg(feature="pdqsort")]
     #[test]
-    fn test_sort_numbers() {
-        let mut rng = thread_rng();
-        for n in 0..16 {
-            for l in 0..16 {
-                let mut v = rng.gen_iter::<f64>()
-                    .map(|x| x % (1 << l) as i64 as f64)
-                    .take((1 << n))
-                    .collect::<Vec<_>>();
-                let mut v1 = v.clone();
+    fn test_try_from_f64() {
+        use self::std::convert::TryFrom;
+        use super::OverflowError;
+        let narrow = |x: f64| FloatOrd::<f32>::try_from(FloatOrd(x));
+        assert_eq!(narrow(1.5), Ok(FloatOrd(1.5f32)));
+        assert_eq!(narrow(-0.0).unwrap().0.to_bits(), (-0.0f32).to_bits());
+        assert_eq!(narrow(0.1), Ok(FloatOrd(0.1f32)));
+        assert_eq!(narrow(1e-50), Ok(FloatOrd(0.0f32)));
+        assert_eq!(narrow(f32::MAX as f64), Ok(FloatOrd(f32::MAX)));
+        assert_eq!(narrow(1e40), Err(OverflowError));
+        assert_eq!(narrow(-1e40), Err(OverflowError));
+        assert_eq!(narrow(f64::MAX), Err(OverflowError));
+        assert_eq!(narrow(f64::INFINITY), Ok(FloatOrd(f32::INFINITY)));
+        assert_eq!(narrow(-f64::INFINITY), Ok(FloatOrd(-f32::INFINITY)));
+        assert!(narrow(f64::NAN).unwrap().is_nan());
+        assert_eq!(OverflowError.to_string(), "value is out of range");
+    }
 
-                super::sort(&mut v);
-                assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    #[test]
+    fn test_abs() {
+        let x: FloatOrd<f64> = FloatOrd(-2.0f64).abs();
+        assert_eq!(x, FloatOrd(2.0));
+        assert_eq!(FloatOrd(-0.0f32).abs(), FloatOrd(0.0));
+        assert_eq!(FloatOrd(-f64::INFINITY).abs(), FloatOrd(f64::INFINITY));
+        assert_eq!(FloatOrd(-f64::NAN).abs(), FloatOrd(f64::NAN));
+    }
 
-                v1.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                assert!(v1.windows(2).all(|w| w[0] <= w[1]));
+    #[test]
+    fn test_saturating() {
+        let max = FloatOrd(f64::MAX);
+        assert_eq!(max.saturating_add(max), max);
+        assert_eq!((-max).saturating_add(-max), FloatOrd(f64::MIN));
+        assert_eq!(max.saturating_mul(FloatOrd(2.0)), max);
+        assert_eq!(max.saturating_mul(FloatOrd(-2.0)), FloatOrd(f64::MIN));
+        assert_eq!(FloatOrd(f32::MAX).saturating_mul(FloatOrd(f32::MAX)), FloatOrd(f32::MAX));
+        assert_eq!(FloatOrd(1.5f64).saturating_add(FloatOrd(2.0)), FloatOrd(3.5));
+        assert_eq!(FloatOrd(1.5f32).saturating_mul(FloatOrd(-2.0)), FloatOrd(-3.0));
+        let inf = FloatOrd(f64::INFINITY);
+        assert_eq!(inf.saturating_add(FloatOrd(1.0)), inf);
+        assert_eq!(FloatOrd(2.0f64).saturating_mul(-inf), -inf);
+        assert!(inf.saturating_add(-inf).is_nan());
+        assert!(FloatOrd(f64::NAN).saturating_mul(max).is_nan());
+    }
 
-                v1.sort_by(|a, b| b.partial_cmp(a).unwrap());
-                assert!(v1.windows(2).all(|w| w[0] >= w[1]));
-            }
-        }
+    #[test]
+    fn test_checked_div() {
+        let one = FloatOrd(1.0f64);
+        let zero = FloatOrd(0.0f64);
+        assert_eq!(one.checked_div(zero), None);
+        assert_eq!((-one).checked_div(-zero), None);
+        assert_eq!(zero.checked_div(zero), None);
+        assert_eq!(FloatOrd(f64::MAX).checked_div(FloatOrd(0.5)), None);
+        assert_eq!(FloatOrd(3.0f64).checked_div(FloatOrd(2.0)), Some(FloatOrd(1.5)));
+        assert_eq!(FloatOrd(1.0f32).checked_div(FloatOrd(-4.0)), Some(FloatOrd(-0.25)));
+        assert_eq!(zero.checked_div(one), Some(zero));
+        let inf = FloatOrd(f64::INFINITY);
+        assert_eq!(inf.checked_div(zero), Some(inf));
+        assert_eq!(one.checked_div(inf), Some(zero));
+        assert!(inf.checked_div(inf).unwrap().is_nan());
+        assert!(FloatOrd(f32::NAN).checked_div(FloatOrd(1.0)).unwrap().is_nan());
+    }
 
-        let mut v = [5.0];
-        super::sort(&mut v);
-        assert!(v == [5.0]);
+    #[test]
+    fn test_recip() {
+        let x: FloatOrd<f64> = FloatOrd(2.0f64).recip();
+        assert_eq!(x, FloatOrd(0.5));
+        assert_eq!(FloatOrd(-4.0f32).recip(), FloatOrd(-0.25));
+        assert_eq!(FloatOrd(-0.0f64).recip(), FloatOrd(-f64::INFINITY));
+        assert_eq!(FloatOrd(f64::INFINITY).recip(), FloatOrd(0.0));
     }
 
-    #[cfg(feature="pdqsort")]
+    #[cfg(any(feature="std", feature="libm"))]
     #[test]
-    fn test_sort_nan() {
-        let nan = ::core::f64::NAN;
-        let mut v = [-1.0, 5.0, 0.0, -0.0, nan, 1.5, nan, 3.7];
-        super::sort(&mut v);
-        assert!(v[0] == -1.0);
-        assert!(v[1] == 0.0 && v[1].is_sign_negative());
-        assert!(v[2] == 0.0 && !v[2].is_sign_negative());
-        assert!(v[3] == 1.5);
-        assert!(v[4] == 3.7);
-        assert!(v[5] == 5.0);
-        assert!(v[6].is_nan());
-        assert!(v[7].is_nan());
+    fn test_pow() {
+        let x: FloatOrd<f64> = FloatOrd(2.0f64).powi_wrapped(3);
+        assert_eq!(x, FloatOrd(8.0));
+        assert_eq!(FloatOrd(2.0f32).powi_wrapped(-2), FloatOrd(0.25));
+        let y: FloatOrd<f64> = FloatOrd(9.0f64).powf_wrapped(0.5);
+        assert_eq!(y, FloatOrd(3.0));
+        assert_eq!(FloatOrd(8.0f32).powf_wrapped(2.0), FloatOrd(64.0));
+        assert_eq!(FloatOrd(f64::NAN).powi_wrapped(0), FloatOrd(1.0));
     }
 
+    #[cfg(all(feature="libm", not(feature="std")))]
     #[test]
-    fn test_add() {
-        assert_eq!(FloatOrd(1.5) + FloatOrd(1.5), FloatOrd(1.5 + 1.5));
-        assert_eq!(1.5 + FloatOrd(1.5), FloatOrd(1.5 + 1.5));
-        assert_eq!(FloatOrd(1.5) + 1.5, FloatOrd(1.5 + 1.5));
+    fn test_libm_sqrt() {
+        assert_eq!(FloatOrd(4.0f64).sqrt_wrapped(), FloatOrd(2.0));
+        assert_eq!(FloatOrd(4.0f32).sqrt_wrapped(), FloatOrd(2.0));
+    }
+
+    #[cfg(any(feature="std", feature="libm"))]
+    #[test]
+    fn test_math() {
+        let x: FloatOrd<f64> = FloatOrd(16.0f64).sqrt_wrapped();
+        assert_eq!(x, FloatOrd(4.0));
+        assert_eq!(FloatOrd(2.5f64).floor_wrapped(), FloatOrd(2.0));
+        assert_eq!(FloatOrd(-2.5f32).floor_wrapped(), FloatOrd(-3.0));
+        assert_eq!(FloatOrd(2.1f64).ceil_wrapped(), FloatOrd(3.0));
+        assert_eq!(FloatOrd(-0.5f64).ceil_wrapped(), FloatOrd(-0.0));
+        assert_eq!(FloatOrd(2.5f32).round_wrapped(), FloatOrd(3.0));
+        assert_eq!(FloatOrd(-2.5f64).round_wrapped(), FloatOrd(-3.0));
+        assert!(FloatOrd(-1.0f64).sqrt_wrapped().is_nan());
+        assert_eq!(FloatOrd(-16.0f64).abs().sqrt_wrapped().floor_wrapped(), FloatOrd(4.0));
+        // The float's own methods are still reached through `Deref`.
+        let y: f64 = FloatOrd(2.5f64).floor();
+        assert_eq!(y, 2.0);
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_neg() {
+        assert_eq!(-FloatOrd(1.5), FloatOrd(-1.5));
+        assert_eq!(-FloatOrd(-1.5f32), FloatOrd(1.5f32));
+        assert_eq!(-FloatOrd(f64::INFINITY), FloatOrd(-f64::INFINITY));
+        assert_eq!(-FloatOrd(-f32::INFINITY), FloatOrd(f32::INFINITY));
+        assert!((-FloatOrd(0.0f64)).is_sign_negative());
+        assert!(-FloatOrd(0.0f64) < FloatOrd(0.0f64));
+        assert!(-FloatOrd(0.0f32) < FloatOrd(0.0f32));
+        assert!(-FloatOrd(f64::NAN) < FloatOrd(f64::NAN));
+        assert!(-FloatOrd(f32::NAN) < FloatOrd(f32::NAN));
     }
 
     #[test]
@@ -275,4 +4899,735 @@ mod tests {
         assert_eq!(f.ceil(), 3.0);
         assert_eq!(f.round(), 3.0);
     }
+
+    #[test]
+    fn test_as_ref_mut() {
+        fn double<F: AsRef<f64>>(f: F) -> f64 {
+            f.as_ref() * 2.0
+        }
+        assert_eq!(double(FloatOrd(1.5)), 3.0);
+
+        let mut f = FloatOrd(1.0f64);
+        assert!(f < FloatOrd(2.0));
+        *f.as_mut() = 3.0;
+        assert!(f > FloatOrd(2.0));
+        *f.as_mut() = -0.0;
+        assert!(f < FloatOrd(0.0));
+    }
+
+    #[test]
+    fn test_fmt() {
+        assert_eq!(format!("{}", FloatOrd(1.5)), "1.5");
+        assert_eq!(format!("{:.3}", FloatOrd(1.23456)), "1.235");
+        assert_eq!(format!("{:8.2}", FloatOrd(1.23456f32)), "    1.23");
+        assert_eq!(format!("{:<6}|", FloatOrd(-0.5)), "-0.5  |");
+        assert_eq!(format!("{:+}", FloatOrd(2.0)), "+2");
+        assert_eq!(format!("{}", FloatOrd(-0.0)), "-0");
+        assert_eq!(format!("{}", FloatOrd(f64::NAN)), "NaN");
+        assert_eq!(format!("{:e}", FloatOrd(1234.5)), "1.2345e3");
+        assert_eq!(format!("{:.2e}", FloatOrd(1234.5)), "1.23e3");
+        assert_eq!(format!("{:E}", FloatOrd(0.00125f32)), "1.25E-3");
+        assert_eq!(format!("{:+10.1E}", FloatOrd(1234.5)), "    +1.2E3");
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(format!("{:?}", FloatOrd(1.5f64)), "FloatOrd(1.5)");
+        assert_eq!(format!("{:?}", FloatOrd(-0.0f64)), "FloatOrd(-0.0)");
+        assert_eq!(format!("{:?}", FloatOrd(0.0f32)), "FloatOrd(+0.0)");
+        assert_eq!(format!("{:?}", FloatOrd(-f64::NAN)),
+                   format!("FloatOrd(NaN{{sign:-, bits:{:#x}}})", (-f64::NAN).to_bits()));
+        assert_eq!(format!("{:?}", FloatOrd(f32::from_bits(0x7fc0_0001))),
+                   "FloatOrd(NaN{sign:+, bits:0x7fc00001})");
+        assert_eq!(format!("{:?}", FloatOrd([1.0f64, -0.0])), "FloatOrd([1.0, -0.0])");
+        assert_eq!(format!("{:?}", Some(FloatOrd(f64::INFINITY))), "Some(FloatOrd(inf))");
+        // Types built from floats still format, with the same detail.
+        assert_eq!(format!("{:?}", FloatOrd([0.5f32, 0.0, -f32::NAN])),
+                   "FloatOrd([0.5, +0.0, NaN{sign:-, bits:0xffc00000}])");
+        assert_eq!(format!("{:?}", [FloatOrd(1.0f64), FloatOrd(-0.0)]),
+                   "[FloatOrd(1.0), FloatOrd(-0.0)]");
+        assert_eq!(format!("{:?}", FloatOrd::<[f64; 0]>([])), "FloatOrd([])");
+        // Formatter flags are passed on.
+        assert_eq!(format!("{:#?}", FloatOrd(1.5f64)), "FloatOrd(\n    1.5,\n)");
+        assert_eq!(format!("{:#?}", FloatOrd(-0.0f32)), "FloatOrd(\n    -0.0,\n)");
+        assert_eq!(format!("{:#?}", FloatOrd([1.0f64])), "FloatOrd(\n    [\n        1.0,\n    ],\n)");
+        assert_eq!(format!("{:.1?}", FloatOrd(1.75f64)), "FloatOrd(1.8)");
+    }
+
+    #[test]
+    fn test_into_inner() {
+        let f = FloatOrd(-0.0f64);
+        assert_eq!(f.get().to_bits(), (-0.0f64).to_bits());
+        assert_eq!(f.into_inner().to_bits(), (-0.0f64).to_bits());
+        assert_eq!(FloatOrd(1.5f32).into_inner(), 1.5);
+        assert!(FloatOrd(f64::NAN).get().is_nan());
+    }
+
+    #[test]
+    fn test_layout() {
+        use self::std::mem::{align_of, size_of};
+        assert_eq!(size_of::<FloatOrd<f64>>(), size_of::<f64>());
+        assert_eq!(align_of::<FloatOrd<f64>>(), align_of::<f64>());
+        assert_eq!(size_of::<FloatOrd<f32>>(), size_of::<f32>());
+        assert_eq!(align_of::<FloatOrd<f32>>(), align_of::<f32>());
+        assert_eq!(size_of::<[FloatOrd<f32>; 3]>(), size_of::<[f32; 3]>());
+        assert_eq!(size_of::<FloatOrd<[f64; 2]>>(), size_of::<[f64; 2]>());
+    }
+
+    #[test]
+    fn test_ord_slice() {
+        use super::{as_ord_slice, as_ord_slice_mut, from_ord_slice, from_ord_slice_mut};
+        let nan = f64::NAN;
+        let mut v = [2.0, nan, -0.0, 0.0, -1.0];
+        as_ord_slice_mut(&mut v).sort();
+        assert_eq!(v[0], -1.0);
+        assert!(v[1] == 0.0 && v[1].is_sign_negative());
+        assert!(v[2] == 0.0 && v[2].is_sign_positive());
+        assert_eq!(v[3], 2.0);
+        assert!(v[4].is_nan());
+        assert_eq!(as_ord_slice(&v)[3], FloatOrd(2.0));
+        assert_eq!(as_ord_slice(&v).len(), v.len());
+
+        let mut w = [FloatOrd(1.0f32), FloatOrd(2.0)];
+        from_ord_slice_mut(&mut w)[0] = 3.0;
+        assert_eq!(w[0], FloatOrd(3.0));
+        assert_eq!(from_ord_slice(&w), &[3.0, 2.0]);
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_from_ref() {
+        use self::std::collections::HashMap;
+        let mut map = HashMap::new();
+        map.insert(FloatOrd(1.5f64), 'a');
+        map.insert(FloatOrd(-0.0f64), 'b');
+        map.insert(FloatOrd(f64::NAN), 'c');
+        assert_eq!(map.get(FloatOrd::from_ref(&1.5)), Some(&'a'));
+        assert_eq!(map.get(FloatOrd::from_ref(&-0.0)), Some(&'b'));
+        assert_eq!(map.get(FloatOrd::from_ref(&0.0)), None);
+        assert_eq!(map.get(FloatOrd::from_ref(&f64::NAN)), Some(&'c'));
+        assert_eq!(map.get(FloatOrd::from_ref(&-f64::NAN)), None);
+        assert_eq!(map.get(FloatOrd::from_ref(&2.0)), None);
+
+        let mut x = 1.0f32;
+        *FloatOrd::from_mut(&mut x) += 1.0;
+        assert_eq!(x, 2.0);
+    }
+
+    #[cfg(feature="half")]
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_ord_f16() {
+        use half::f16;
+        let sub = f16::from_bits(1);
+        assert!(FloatOrd(f16::from_f32(1.0)) < FloatOrd(f16::from_f32(2.0)));
+        assert!(FloatOrd(f16::ONE) == FloatOrd(f16::ONE));
+        assert!(FloatOrd(f16::ZERO) > FloatOrd(f16::NEG_ZERO));
+        assert!(FloatOrd(sub) > FloatOrd(f16::ZERO));
+        assert!(FloatOrd(-sub) < FloatOrd(f16::NEG_ZERO));
+        assert!(FloatOrd(sub) < FloatOrd(f16::MIN_POSITIVE));
+        assert!(FloatOrd(f16::NAN) == FloatOrd(f16::NAN));
+        assert!(FloatOrd(-f16::NAN) < FloatOrd(f16::NAN));
+        assert!(FloatOrd(f16::NEG_INFINITY) < FloatOrd(f16::INFINITY));
+        assert!(FloatOrd(f16::MAX) < FloatOrd(f16::INFINITY));
+        assert!(FloatOrd(f16::INFINITY) < FloatOrd(f16::NAN));
+        assert!(FloatOrd(-f16::NAN) < FloatOrd(f16::NEG_INFINITY));
+    }
+
+    #[cfg(feature="half")]
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_hash_f16() {
+        use half::f16;
+        let sub = f16::from_bits(1);
+        assert_ne!(hash(FloatOrd(f16::ZERO)), hash(FloatOrd(f16::NEG_ZERO)));
+        assert_eq!(hash(FloatOrd(f16::NEG_ZERO)), hash(FloatOrd(f16::NEG_ZERO)));
+        assert_ne!(hash(FloatOrd(sub)), hash(FloatOrd(-sub)));
+        assert_eq!(hash(FloatOrd(sub)), hash(FloatOrd(f16::from_bits(1))));
+        assert_ne!(hash(FloatOrd(f16::NAN)), hash(FloatOrd(-f16::NAN)));
+        assert_eq!(hash(FloatOrd(f16::NAN)), hash(FloatOrd(f16::NAN)));
+    }
+
+    #[cfg(all(feature="half", any(feature="pdqsort", feature="alloc")))]
+    #[test]
+    fn test_sort_f16() {
+        use half::f16;
+        let mut v = [f16::NAN, f16::ONE, f16::ZERO, f16::NEG_ZERO, f16::NEG_INFINITY];
+        super::sort(&mut v);
+        assert!(v[0] == f16::NEG_INFINITY);
+        assert!(v[1] == f16::ZERO && v[1].is_sign_negative());
+        assert!(v[2] == f16::ZERO && v[2].is_sign_positive());
+        assert!(v[3] == f16::ONE);
+        assert!(v[4].is_nan());
+    }
+
+    #[cfg(feature="half")]
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_ord_bf16() {
+        use half::bf16;
+        let sub = bf16::from_bits(1);
+        assert!(FloatOrd(bf16::from_f32(1.0)) < FloatOrd(bf16::from_f32(2.0)));
+        assert!(FloatOrd(bf16::ONE) == FloatOrd(bf16::ONE));
+        assert!(FloatOrd(bf16::ZERO) > FloatOrd(bf16::NEG_ZERO));
+        assert!(FloatOrd(sub) > FloatOrd(bf16::ZERO));
+        assert!(FloatOrd(-sub) < FloatOrd(bf16::NEG_ZERO));
+        assert!(FloatOrd(sub) < FloatOrd(bf16::MIN_POSITIVE));
+        assert!(FloatOrd(bf16::NAN) == FloatOrd(bf16::NAN));
+        assert!(FloatOrd(-bf16::NAN) < FloatOrd(bf16::NAN));
+        assert!(FloatOrd(bf16::NEG_INFINITY) < FloatOrd(bf16::INFINITY));
+        assert!(FloatOrd(bf16::MAX) < FloatOrd(bf16::INFINITY));
+        assert!(FloatOrd(bf16::INFINITY) < FloatOrd(bf16::NAN));
+        assert!(FloatOrd(-bf16::NAN) < FloatOrd(bf16::NEG_INFINITY));
+        // bf16 has the exponent range of f32.
+        assert!(FloatOrd(bf16::from_f32(1e38)) < FloatOrd(bf16::MAX));
+        let tiny = FloatOrd(bf16::from_f32(1e-38));
+        assert!(FloatOrd(sub) < tiny && tiny < FloatOrd(bf16::MIN_POSITIVE));
+        assert_eq!(FloatOrd(bf16::MAX).next_up().0, bf16::INFINITY);
+    }
+
+    #[cfg(feature="half")]
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_hash_bf16() {
+        use half::bf16;
+        let sub = bf16::from_bits(1);
+        assert_ne!(hash(FloatOrd(bf16::ZERO)), hash(FloatOrd(bf16::NEG_ZERO)));
+        assert_ne!(hash(FloatOrd(sub)), hash(FloatOrd(-sub)));
+        assert_ne!(hash(FloatOrd(bf16::NAN)), hash(FloatOrd(-bf16::NAN)));
+        assert_eq!(hash(FloatOrd(bf16::NAN)), hash(FloatOrd(bf16::NAN)));
+    }
+
+    #[cfg(all(feature="half", any(feature="pdqsort", feature="alloc")))]
+    #[test]
+    fn test_sort_bf16() {
+        use half::bf16;
+        let sub = bf16::from_bits(1);
+        let mut v = [bf16::NAN, bf16::ONE, sub, bf16::ZERO, bf16::NEG_ZERO, bf16::NEG_INFINITY];
+        super::sort(&mut v);
+        assert!(v[0] == bf16::NEG_INFINITY);
+        assert!(v[1] == bf16::ZERO && v[1].is_sign_negative());
+        assert!(v[2] == bf16::ZERO && v[2].is_sign_positive());
+        assert!(v[3] == sub);
+        assert!(v[4] == bf16::ONE);
+        assert!(v[5].is_nan());
+    }
+
+    #[test]
+    fn test_array() {
+        use self::std::collections::BTreeSet;
+        assert!(FloatOrd([1.0f64, 2.0]) < FloatOrd([1.0, 3.0]));
+        assert!(FloatOrd([1.0f64, 5.0]) < FloatOrd([2.0, 0.0]));
+        assert!(FloatOrd([1.0f32, 2.0]) == FloatOrd([1.0, 2.0]));
+        assert!(FloatOrd([1.0f64, -0.0]) < FloatOrd([1.0, 0.0]));
+        assert!(FloatOrd([1.0f64, -0.0]) != FloatOrd([1.0, 0.0]));
+        assert!(FloatOrd([f64::NAN, 0.0]) == FloatOrd([f64::NAN, 0.0]));
+        assert!(FloatOrd([f64::INFINITY, 0.0]) < FloatOrd([f64::NAN, -1.0]));
+        assert!(FloatOrd([0.0f64, 1.0, -0.0]) < FloatOrd([0.0, 1.0, 0.0]));
+        assert!(FloatOrd([0.0f32, 1.0, 2.0]) > FloatOrd([-0.0, 4.0, 5.0]));
+        assert!(FloatOrd([0.0f64, f64::NAN, 2.0]) == FloatOrd([0.0, f64::NAN, 2.0]));
+        assert_ne!(hash(FloatOrd([1.0f64, -0.0])), hash(FloatOrd([1.0f64, 0.0])));
+        assert_eq!(hash(FloatOrd([1.0f64, f64::NAN])), hash(FloatOrd([1.0f64, f64::NAN])));
+
+        let set = [[0.0, 1.0], [-0.0, 1.0], [0.0, 1.0], [-1.0, 3.0]]
+            .iter()
+            .map(|&p| FloatOrd(p))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(set.len(), 3);
+        assert!(set.iter().next().unwrap().0 == [-1.0, 3.0]);
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_min_float() {
+        use self::std::collections::BinaryHeap;
+        use super::MinFloat;
+        let nan = f64::NAN;
+        let mut heap = BinaryHeap::new();
+        for &x in &[3.0, nan, -0.0, -nan, 0.0, -f64::INFINITY, 1.0] {
+            heap.push(MinFloat(x));
+        }
+        let v = heap.into_sorted_vec().into_iter().rev().map(|x| x.0).collect::<Vec<_>>();
+        assert!(v[0].is_nan() && v[0].is_sign_negative());
+        assert_eq!(v[1], -f64::INFINITY);
+        assert!(v[2] == 0.0 && v[2].is_sign_negative());
+        assert!(v[3] == 0.0 && v[3].is_sign_positive());
+        assert_eq!(v[4], 1.0);
+        assert_eq!(v[5], 3.0);
+        assert!(v[6].is_nan() && v[6].is_sign_positive());
+
+        let mut heap = BinaryHeap::new();
+        heap.push(MinFloat(2.0f32));
+        heap.push(MinFloat(f32::NAN));
+        heap.push(MinFloat(1.0f32));
+        assert_eq!(heap.pop().unwrap().0, 1.0);
+        assert_eq!(heap.pop().unwrap().0, 2.0);
+        assert!(heap.pop().unwrap().0.is_nan());
+        assert!(heap.pop().is_none());
+
+        assert!(MinFloat(1.0f64) > MinFloat(2.0));
+        assert!(MinFloat(-0.0f64) != MinFloat(0.0));
+        assert_eq!(hash(MinFloat(1.5f64)), hash(FloatOrd(1.5f64)));
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_opt_float_ord() {
+        use super::OptFloatOrd;
+        let nan = f64::NAN;
+        let mut v = [Some(nan), Some(1.0), None, Some(-0.0), Some(-nan), None,
+                     Some(-f64::INFINITY), Some(0.0)]
+            .iter()
+            .map(|&x| OptFloatOrd(x))
+            .collect::<Vec<_>>();
+        v.sort();
+        assert!(v[0].0.is_none() && v[1].0.is_none());
+        assert!(v[2].0.unwrap().is_nan() && v[2].0.unwrap().is_sign_negative());
+        assert_eq!(v[3].0, Some(-f64::INFINITY));
+        assert!(v[4].0.unwrap().is_sign_negative());
+        assert!(v[5].0.unwrap().is_sign_positive());
+        assert_eq!(v[6].0, Some(1.0));
+        assert!(v[7].0.unwrap().is_nan() && v[7].0.unwrap().is_sign_positive());
+
+        assert!(OptFloatOrd(None::<f32>) == OptFloatOrd(None));
+        assert!(OptFloatOrd(Some(f32::NAN)) == OptFloatOrd(Some(f32::NAN)));
+        assert!(OptFloatOrd(Some(-0.0f32)) != OptFloatOrd(Some(0.0)));
+        assert_eq!(hash(OptFloatOrd(Some(nan))), hash(OptFloatOrd(Some(nan))));
+        assert_ne!(hash(OptFloatOrd(Some(0.0f64))), hash(OptFloatOrd(Some(-0.0f64))));
+        assert_ne!(hash(OptFloatOrd(None::<f64>)), hash(OptFloatOrd(Some(0.0f64))));
+    }
+
+    #[test]
+    fn test_float_ord_pair() {
+        use super::FloatOrdPair;
+        use self::std::collections::BTreeMap;
+        let nan = f64::NAN;
+        let keys = [(1.0, nan), (nan, -1.0), (0.0, 2.0), (-0.0, 3.0), (1.0, 0.5),
+                    (-f64::INFINITY, nan), (1.0, -0.0)];
+        let mut map = BTreeMap::new();
+        for (i, &(a, b)) in keys.iter().enumerate() {
+            map.insert(FloatOrdPair(a, b), i);
+        }
+        assert_eq!(map.values().cloned().collect::<Vec<_>>(), [5, 3, 2, 6, 4, 0, 1]);
+        assert_eq!(map.get(&FloatOrdPair(nan, -1.0)), Some(&1));
+        assert_eq!(map.get(&FloatOrdPair(0.0, 3.0)), None);
+
+        assert!(FloatOrdPair(-0.0f32, 9.0) < FloatOrdPair(0.0, -9.0));
+        assert!(FloatOrdPair(1.0f32, nan as f32) == FloatOrdPair(1.0, nan as f32));
+        assert_eq!(hash(FloatOrdPair(1.0, nan)), hash(FloatOrdPair(1.0, nan)));
+        assert_ne!(hash(FloatOrdPair(-0.0, 1.0)), hash(FloatOrdPair(0.0, 1.0)));
+    }
+
+    #[test]
+    fn test_float_interval() {
+        use super::FloatInterval;
+        let zeros = FloatInterval { lo: -0.0, hi: 0.0 };
+        assert!(zeros.contains(&-0.0) && zeros.contains(&0.0));
+        assert!(!zeros.contains(&f64::MIN_POSITIVE));
+        let negative = FloatInterval { lo: -f64::INFINITY, hi: -0.0 };
+        assert!(negative.contains(&-0.0));
+        assert!(!negative.contains(&0.0));
+        assert!(negative.contains(&-f64::INFINITY));
+        let positive = FloatInterval { lo: 0.0f32, hi: f32::INFINITY };
+        assert!(positive.contains(&f32::INFINITY) && positive.contains(&0.0));
+        assert!(!positive.contains(&-0.0));
+        for i in &[zeros, negative, FloatInterval { lo: -1.0, hi: 1.0 }] {
+            assert!(!i.contains(&f64::NAN));
+            assert!(!i.contains(&-f64::NAN));
+        }
+        assert!(!positive.contains(&f32::NAN));
+        assert!(FloatInterval { lo: 1.0, hi: f64::NAN }.contains(&f64::NAN));
+        assert!(!FloatInterval { lo: 1.0, hi: 0.0 }.contains(&0.5));
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_sorted_float_vec() {
+        use super::{SortedFloatVec, is_sorted};
+        let mut rng = thread_rng();
+        let mut v: SortedFloatVec<f64> = rng.gen_iter::<f64>()
+            .map(|x| x - 0.5)
+            .take(50)
+            .collect();
+        assert_eq!(v.len(), 50);
+        assert!(is_sorted(v.as_slice()));
+        for &x in &[f64::NAN, -0.0, 0.0, 0.0, f64::INFINITY, -2.0] {
+            v.insert(x);
+            assert!(is_sorted(v.as_slice()));
+        }
+        assert!(v.as_slice()[0] == -2.0);
+        assert!(v.as_slice().last().unwrap().is_nan());
+        v.extend(vec![1.0, -f64::INFINITY, 0.25]);
+        assert!(is_sorted(v.as_slice()));
+        assert_eq!(v.len(), 59);
+        assert_eq!(v.as_slice()[0], -f64::INFINITY);
+
+        let mut w = SortedFloatVec::<f32>::default();
+        assert!(w.is_empty());
+        w.insert(0.0);
+        w.insert(-0.0);
+        assert!(w.into_vec()[0].is_sign_negative());
+    }
+
+    #[cfg(feature="alloc")]
+    #[test]
+    fn test_sorted_float_vec_extend() {
+        use super::{SortedFloatVec, is_sorted};
+        let mut rng = thread_rng();
+        let mut v: SortedFloatVec<f64> = (0..20).map(|x| x as f64).collect();
+        let mut all = v.as_slice().to_vec();
+        let batches = [vec![], vec![25.0, 21.0], vec![-1.0], vec![f64::NAN, 3.5, -0.0, 3.0],
+                       vec![0.0, 10.0, 10.0], vec![f64::INFINITY]];
+        for batch in batches.iter().cloned()
+                            .chain((0..5).map(|_| rng.gen_iter::<f64>().take(7).collect())) {
+            all.extend(batch.iter().cloned());
+            v.extend(batch);
+            assert!(is_sorted(v.as_slice()));
+            let mut expected = all.clone();
+            expected.sort_by_key(|&x| FloatOrd(x));
+            assert!(v.as_slice().iter().map(|x| x.to_bits())
+                     .eq(expected.iter().map(|x| x.to_bits())));
+        }
+        assert_eq!(v.len(), all.len());
+        assert!(v.as_slice().last().unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_min_max_accumulator() {
+        use super::MinMaxAccumulator;
+        let mut acc = MinMaxAccumulator::<f64>::default();
+        assert_eq!(acc.min(), None);
+        assert_eq!(acc.max(), None);
+        acc.push(3.0);
+        assert_eq!((acc.min(), acc.max()), (Some(3.0), Some(3.0)));
+        for &x in &[0.0, f64::NAN, -0.0, f64::INFINITY, -2.0] {
+            acc.push(x);
+        }
+        assert_eq!(acc.min(), Some(-2.0));
+        assert!(acc.max().unwrap().is_nan());
+
+        let mut acc = MinMaxAccumulator::new();
+        acc.push(0.0f32);
+        acc.push(-0.0);
+        assert!(acc.min().unwrap().is_sign_negative());
+        assert!(acc.max().unwrap().is_sign_positive());
+    }
+
+    #[test]
+    fn test_nan_lowest() {
+        use super::NanLowest;
+        let nan = f64::NAN;
+        let payload = f64::from_bits(nan.to_bits() | 1);
+        let mut v = [2.0, nan, -0.0, -f64::INFINITY, -nan, 0.0, f64::INFINITY, payload]
+            .iter()
+            .map(|&x| NanLowest(x))
+            .collect::<Vec<_>>();
+        v.sort();
+        assert!(v[..3].iter().all(|x| x.0.is_nan()));
+        assert_eq!(v[3].0, -f64::INFINITY);
+        assert!(v[4].0 == 0.0 && v[4].0.is_sign_negative());
+        assert!(v[5].0 == 0.0 && v[5].0.is_sign_positive());
+        assert_eq!(v[6].0, 2.0);
+        assert_eq!(v[7].0, f64::INFINITY);
+
+        assert!(NanLowest(nan) == NanLowest(-nan));
+        assert!(NanLowest(f32::NAN) < NanLowest(f32::MIN));
+        assert_eq!(hash(NanLowest(nan)), hash(NanLowest(-nan)));
+        assert_eq!(hash(NanLowest(nan)), hash(NanLowest(payload)));
+        assert_ne!(hash(NanLowest(-0.0f64)), hash(NanLowest(0.0f64)));
+    }
+
+    #[test]
+    fn test_to_int_saturating() {
+        assert_eq!(FloatOrd(f64::INFINITY).to_i64_saturating(), i64::MAX);
+        assert_eq!(FloatOrd(f64::NEG_INFINITY).to_i64_saturating(), i64::MIN);
+        assert_eq!(FloatOrd(f64::NAN).to_i64_saturating(), 0);
+        assert_eq!(FloatOrd(-f64::NAN).to_i64_saturating(), 0);
+        assert_eq!(FloatOrd(-2.7f64).to_i64_saturating(), -2);
+        assert_eq!(FloatOrd(1e19f64).to_i64_saturating(), i64::MAX);
+
+        assert_eq!(FloatOrd(f64::INFINITY).to_u64_saturating(), u64::MAX);
+        assert_eq!(FloatOrd(f64::NEG_INFINITY).to_u64_saturating(), 0);
+        assert_eq!(FloatOrd(f64::NAN).to_u64_saturating(), 0);
+        assert_eq!(FloatOrd(-2.7f64).to_u64_saturating(), 0);
+        assert_eq!(FloatOrd(2.7f64).to_u64_saturating(), 2);
+        assert_eq!(FloatOrd(1e19f64).to_u64_saturating(), 10_000_000_000_000_000_000);
+    }
+
+    #[cfg(any(feature="pdqsort", feature="alloc"))]
+    #[test]
+    fn test_sorted_view() {
+        use super::sorted_view;
+        let nan = f64::NAN;
+        let mut v = [3.0, nan, -0.0, f64::INFINITY, -2.0, 0.0];
+        let view = sorted_view(&mut v);
+        assert_eq!(view.len(), 6);
+        assert!(!view.is_empty());
+        assert_eq!(view.get(0), Some(&-2.0));
+        assert!(view.get(1).unwrap().is_sign_negative());
+        assert!(view.get(2).unwrap().is_sign_positive());
+        assert_eq!(view.get(6), None);
+        let mut it = view.iter();
+        assert_eq!(it.nth(3), Some(&3.0));
+        assert_eq!(it.next(), Some(&f64::INFINITY));
+        assert!(it.next().unwrap().is_nan());
+        assert_eq!(it.next(), None);
+        assert!(view.into_iter().zip(view.iter().skip(1))
+                    .all(|(a, b)| FloatOrd(*a) <= FloatOrd(*b)));
+        assert!(view.as_slice()[5].is_nan());
+        assert!(v[5].is_nan());
+
+        let mut empty: [f32; 0] = [];
+        assert!(sorted_view(&mut empty).is_empty());
+    }
+
+    #[test]
+    fn test_to_f64_to_f32() {
+        let sub = f32::from_bits(1);
+        assert_eq!(FloatOrd(sub).to_f64(), 2f64.powi(-149));
+        assert_eq!(FloatOrd(sub).to_f64() as f32, sub);
+        assert_eq!(FloatOrd(-0.0f32).to_f64().to_bits(), (-0.0f64).to_bits());
+        assert_eq!(FloatOrd(f32::MAX).to_f64(), f32::MAX as f64);
+        assert!(FloatOrd(f32::NAN).to_f64().is_nan());
+        assert!(FloatOrd(f32::NAN).to_f64().is_sign_positive());
+        assert!(FloatOrd(-f32::NAN).to_f64().is_sign_negative());
+
+        assert_eq!(FloatOrd(1e300f64).to_f32(), f32::INFINITY);
+        assert_eq!(FloatOrd(-1e300f64).to_f32(), f32::NEG_INFINITY);
+        assert_eq!(FloatOrd(1.5f64).to_f32(), 1.5);
+        assert_eq!(FloatOrd(-1e-300f64).to_f32().to_bits(), (-0.0f32).to_bits());
+        assert!(FloatOrd(f64::NAN).to_f32().is_nan());
+        assert!(FloatOrd(f64::NAN).to_f32().is_sign_positive());
+        assert!(FloatOrd(-f64::NAN).to_f32().is_sign_negative());
+    }
+
+    #[test]
+    #[cfg(not(feature="nan-at-top"))]
+    fn test_to_f64_to_f32_nan_payload() {
+        let nans = [-f32::from_bits(0x7fc0_0001), -f32::NAN, f32::from_bits(0x7f80_0001),
+                    f32::NAN, f32::from_bits(0x7fc0_0001), f32::from_bits(0x7fff_ffff)];
+        for &x in &nans {
+            let wide = FloatOrd(x).to_f64();
+            assert!(wide.is_nan());
+            assert_eq!(FloatOrd(wide).to_f32().to_bits(), x.to_bits());
+        }
+        // Widening keeps the order of the NaNs.
+        for w in nans.windows(2) {
+            assert!(FloatOrd(w[0]) < FloatOrd(w[1]));
+            assert!(FloatOrd(FloatOrd(w[0]).to_f64()) < FloatOrd(FloatOrd(w[1]).to_f64()));
+        }
+        // A payload only in the low bits sets the quiet bit when narrowed.
+        let low = FloatOrd(f64::from_bits(0xfff0_0000_0000_0001)).to_f32();
+        assert_eq!(low.to_bits(), 0xffc0_0000);
+    }
+
+    #[test]
+    fn test_rev_float_ord() {
+        use self::std::collections::BinaryHeap;
+        use super::RevFloatOrd;
+        let mut v = [2.0, -0.0, f64::INFINITY, -1.0, f64::NAN, 0.0, -f64::INFINITY]
+            .iter()
+            .map(|&x| RevFloatOrd(x))
+            .collect::<Vec<_>>();
+        v.sort();
+        assert!(v[0].is_nan());
+        assert_eq!(*v[1], f64::INFINITY);
+        assert_eq!(*v[2], 2.0);
+        assert!(*v[3] == 0.0 && v[3].is_sign_positive());
+        assert!(*v[4] == 0.0 && v[4].is_sign_negative());
+        assert_eq!(*v[5], -1.0);
+        assert_eq!(*v[6], -f64::INFINITY);
+        assert!(v.windows(2).all(|w| FloatOrd(*w[0]) >= FloatOrd(*w[1])));
+
+        assert!(RevFloatOrd(1.0f32) > RevFloatOrd(2.0));
+        assert_eq!(RevFloatOrd(1.0f32).cmp(&RevFloatOrd(2.0)),
+                   FloatOrd(2.0f32).cmp(&FloatOrd(1.0)));
+        assert!(RevFloatOrd(f64::NAN) == RevFloatOrd(f64::NAN));
+        assert!(RevFloatOrd(-0.0f64) != RevFloatOrd(0.0));
+        assert_eq!(hash(RevFloatOrd(f64::NAN)), hash(RevFloatOrd(f64::NAN)));
+        assert_eq!(hash(RevFloatOrd(1.5f64)), hash(FloatOrd(1.5f64)));
+        assert_ne!(hash(RevFloatOrd(-0.0f64)), hash(RevFloatOrd(0.0f64)));
+        let xs = [-f64::INFINITY, -0.0, 0.0, 1.0, f64::NAN];
+        for &a in &xs {
+            for &b in &xs {
+                assert_eq!(RevFloatOrd(a).cmp(&RevFloatOrd(b)),
+                           super::MinFloat(a).cmp(&super::MinFloat(b)));
+            }
+        }
+
+        assert_eq!((RevFloatOrd(3.0f64) - RevFloatOrd(1.0)).0, 2.0);
+        assert_eq!((RevFloatOrd(3.0f64) * 2.0).0, 6.0);
+        assert_eq!((-RevFloatOrd(3.0f64)).0, -3.0);
+
+        let mut heap = BinaryHeap::new();
+        heap.extend([3.0, 1.0, 2.0].iter().map(|&x| RevFloatOrd(x)));
+        assert_eq!(heap.pop(), Some(RevFloatOrd(1.0)));
+    }
+
+    #[test]
+    fn test_canonicalize_slice() {
+        use super::canonicalize_slice;
+        use self::std::collections::HashSet;
+        let nan = f64::NAN;
+        let mut v = [0.0, -0.0, nan, -nan, f64::from_bits(nan.to_bits() | 7), 1.0, -1.0];
+        canonicalize_slice(&mut v);
+        let set: HashSet<_> = v.iter().map(|&x| FloatOrd(x)).collect();
+        assert_eq!(set.len(), 4);
+        assert!(set.contains(&FloatOrd(0.0)) && !set.contains(&FloatOrd(-0.0)));
+        assert_eq!(set.iter().filter(|x| x.is_nan()).count(), 1);
+        assert!(set.contains(&FloatOrd(-1.0)));
+
+        let mut w = [-0.0f32, -f32::NAN];
+        canonicalize_slice(&mut w);
+        assert_eq!(w[0].to_bits(), 0);
+        assert_eq!(w[1].to_bits(), f32::NAN.to_bits());
+    }
+
+    #[test]
+    fn test_clamp_slice() {
+        use super::clamp_slice;
+        let mut v = [-2.0, -0.0, 0.0, 0.5, 3.0, f64::NAN, f64::INFINITY, -f64::INFINITY];
+        clamp_slice(&mut v, 0.0, 1.0);
+        assert!(v[..3].iter().all(|x| x.to_bits() == 0));
+        assert_eq!(&v[3..], &[0.5, 1.0, 1.0, 1.0, 0.0]);
+
+        let mut v = [-0.0f32, 0.0, -1.0];
+        clamp_slice(&mut v, -1.0, -0.0);
+        assert!(v[0].is_sign_negative() && v[1].is_sign_negative());
+        assert_eq!(v[2], -1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_clamp_slice_panics() {
+        super::clamp_slice(&mut [1.0f64], 0.0, -0.0);
+    }
+
+    #[test]
+    fn test_float_ord_tolerant() {
+        use super::FloatOrdTolerant;
+        let a = FloatOrd(1.0f64);
+        let b = a.next_up();
+        let c = b.next_up();
+        assert!(a.with_tolerance(1) == b.with_tolerance(1));
+        assert!(a.with_tolerance(0) != b.with_tolerance(0));
+        assert!(a.with_tolerance(0) == b.with_tolerance(1));
+        assert!(a.with_tolerance(0) < b.with_tolerance(0));
+        assert_eq!(a.with_tolerance(1).partial_cmp(&b.with_tolerance(1)), Some(Ordering::Equal));
+        // Not transitive: a == b and b == c, but a != c.
+        assert!(b.with_tolerance(1) == c.with_tolerance(1));
+        assert!(a.with_tolerance(1) != c.with_tolerance(1));
+        assert!(a.with_tolerance(1) < c.with_tolerance(1));
+        assert!(FloatOrd(-0.0f32).with_tolerance(1) == FloatOrd(0.0f32).with_tolerance(1));
+        let nan = FloatOrdTolerant { value: f64::NAN, ulps: 5 };
+        assert!(nan == nan);
+        assert!(nan > FloatOrd(f64::INFINITY).with_tolerance(5));
+    }
+
+    #[test]
+    fn test_canonical_float_ord() {
+        use super::CanonicalFloatOrd;
+        use self::std::collections::HashMap;
+        let nan = f64::NAN;
+        let mut map = HashMap::new();
+        for &x in &[0.0, -0.0, nan, -nan, f64::from_bits(nan.to_bits() | 1), 1.0] {
+            *map.entry(CanonicalFloatOrd(x)).or_insert(0) += 1;
+        }
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[&CanonicalFloatOrd(0.0)], 2);
+        assert_eq!(map[&CanonicalFloatOrd(-nan)], 3);
+        assert_eq!(map[&CanonicalFloatOrd(1.0)], 1);
+
+        assert!(CanonicalFloatOrd(-0.0f32) == CanonicalFloatOrd(0.0));
+        assert!(CanonicalFloatOrd(f32::NAN) == CanonicalFloatOrd(-f32::NAN));
+        assert!(CanonicalFloatOrd(1.0f32) != CanonicalFloatOrd(f32::NAN));
+        assert_eq!(hash(CanonicalFloatOrd(-0.0f64)), hash(CanonicalFloatOrd(0.0f64)));
+    }
+
+    #[cfg(feature="num-complex")]
+    #[test]
+    fn test_cmp_by_magnitude() {
+        use num_complex::Complex;
+        use super::cmp_by_magnitude;
+        let nan = f64::NAN;
+        let mut v = [Complex::new(0.0, -nan), Complex::new(-3.0, 4.0), Complex::new(0.0, 0.0),
+                     Complex::new(-nan, 1.0), Complex::new(0.0, 5.0), Complex::new(1.0, -1.0),
+                     Complex::new(f64::INFINITY, 0.0)];
+        v.sort_by(cmp_by_magnitude);
+        assert_eq!(v[0], Complex::new(0.0, 0.0));
+        assert_eq!(v[1], Complex::new(1.0, -1.0));
+        assert!(v[2..4].contains(&Complex::new(-3.0, 4.0)));
+        assert!(v[2..4].contains(&Complex::new(0.0, 5.0)));
+        assert_eq!(v[4], Complex::new(f64::INFINITY, 0.0));
+        assert!(v[5..].iter().all(|z| z.re.is_nan() || z.im.is_nan()));
+        assert_eq!(cmp_by_magnitude(&Complex::new(-3.0, 4.0), &Complex::new(0.0, 5.0)),
+                   Ordering::Equal);
+        assert_eq!(cmp_by_magnitude(&Complex::new(1.0f32, 0.0), &Complex::new(0.0, -2.0)),
+                   Ordering::Less);
+        // The documented limits of the unscaled squares.
+        assert_eq!(cmp_by_magnitude(&Complex::new(1e153, 0.0), &Complex::new(0.0, 2e153)),
+                   Ordering::Less);
+        assert_eq!(cmp_by_magnitude(&Complex::new(1e155, 0.0), &Complex::new(0.0, 2e155)),
+                   Ordering::Equal);
+        assert_eq!(cmp_by_magnitude(&Complex::new(1e-161, 0.0), &Complex::new(0.0, 2e-161)),
+                   Ordering::Less);
+        assert_eq!(cmp_by_magnitude(&Complex::new(1e-163, 0.0), &Complex::new(0.0, 2e-163)),
+                   Ordering::Equal);
+        assert_eq!(cmp_by_magnitude(&Complex::new(1e18f32, 0.0), &Complex::new(0.0, 2e18)),
+                   Ordering::Less);
+        assert_eq!(cmp_by_magnitude(&Complex::new(1e20f32, 0.0), &Complex::new(0.0, 2e20)),
+                   Ordering::Equal);
+    }
+
+    #[cfg(feature="serde")]
+    #[test]
+    fn test_serde_json() {
+        extern crate serde_json;
+        assert_eq!(serde_json::to_string(&FloatOrd(1.5f64)).unwrap(), "1.5");
+        assert_eq!(serde_json::to_string(&FloatOrd(-2.0f32)).unwrap(), "-2.0");
+        assert_eq!(serde_json::to_string(&[FloatOrd(1.0f64), FloatOrd(0.5)]).unwrap(),
+                   "[1.0,0.5]");
+        let f: FloatOrd<f64> = serde_json::from_str("1.5").unwrap();
+        assert_eq!(f, FloatOrd(1.5));
+    }
+
+    #[cfg(feature="serde")]
+    #[test]
+    fn test_serde_bincode() {
+        extern crate bincode;
+        for &x in &[f64::NAN, -f64::NAN, f64::INFINITY, -f64::INFINITY, -0.0, 0.0] {
+            let bytes = bincode::serialize(&FloatOrd(x)).unwrap();
+            let y: FloatOrd<f64> = bincode::deserialize(&bytes).unwrap();
+            assert_eq!(x.to_bits(), y.0.to_bits());
+        }
+        for &x in &[f32::NAN, -f32::NAN, f32::INFINITY, -f32::INFINITY, -0.0, 0.0] {
+            let bytes = bincode::serialize(&FloatOrd(x)).unwrap();
+            let y: FloatOrd<f32> = bincode::deserialize(&bytes).unwrap();
+            assert_eq!(x.to_bits(), y.0.to_bits());
+        }
+    }
+
+    #[cfg(feature="nan-at-top")]
+    #[test]
+    fn test_nan_at_top() {
+        let payload = f64::from_bits(f64::NAN.to_bits() | 1);
+        assert_eq!(FloatOrd(f64::NAN), FloatOrd(-f64::NAN));
+        assert_eq!(FloatOrd(f64::NAN), FloatOrd(payload));
+        assert_eq!(FloatOrd(f32::NAN), FloatOrd(-f32::NAN));
+        assert_eq!(hash(FloatOrd(f64::NAN)), hash(FloatOrd(-f64::NAN)));
+        assert_eq!(hash(FloatOrd(f64::NAN)), hash(FloatOrd(payload)));
+        assert_eq!(hash(FloatOrd(f32::NAN)), hash(FloatOrd(-f32::NAN)));
+        assert!(FloatOrd(-f64::NAN) > FloatOrd(f64::INFINITY));
+        assert!(FloatOrd(-f32::NAN) > FloatOrd(f32::INFINITY));
+
+        let mut v = [FloatOrd(-f64::NAN), FloatOrd(1.0), FloatOrd(f64::NAN),
+                     FloatOrd(f64::INFINITY), FloatOrd(-f64::INFINITY)];
+        v.sort();
+        assert_eq!(&v[..3], &[FloatOrd(-f64::INFINITY), FloatOrd(1.0), FloatOrd(f64::INFINITY)]);
+        assert!(v[3].is_nan() && v[4].is_nan());
+
+        assert_eq!(FloatOrd(-f64::NAN).total_cmp(&FloatOrd(f64::NAN)), Ordering::Less);
+        assert_eq!(FloatOrd(-f64::NAN).classify_ord(), super::FloatClass::NegativeNan);
+        assert!(FloatOrd::<f64>::from_ord_bits(FloatOrd(-f64::NAN).to_ord_bits()).is_nan());
+    }
 }